@@ -0,0 +1,24 @@
+use crate::alternative::borda::{borda_scores, truncation};
+use crate::alternative::rank_by_elimination;
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::MethodResult;
+
+/// Rank candidates by Baldwin's method: repeatedly recount Borda scores
+/// among the remaining candidates and eliminate the lowest scoring.
+pub fn baldwin(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+) -> MethodResult {
+    rank_by_elimination(candidates, |remaining| {
+        let scores = borda_scores(remaining, ballots, truncation(options));
+        let lowest = remaining.iter().map(|c| scores[c]).min().unwrap();
+
+        remaining
+            .iter()
+            .filter(|c| scores[c] == lowest)
+            .copied()
+            .collect()
+    })
+}
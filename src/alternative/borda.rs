@@ -0,0 +1,81 @@
+use crate::alternative::restricted_ranking;
+use crate::condorcet::rank_by_key;
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::{BordaTruncation, TabulationOptions};
+use crate::model::report::MethodResult;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Return the Borda score of each candidate, counting only rankings of
+/// `candidates`. Scores are doubled so that averaged points stay whole.
+pub fn borda_scores(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    truncation: BordaTruncation,
+) -> HashMap<CandidateId, u64> {
+    let set: HashSet<CandidateId> = candidates.iter().copied().collect();
+    let n = candidates.len() as u64;
+    let mut scores: HashMap<CandidateId, u64> = candidates.iter().map(|c| (*c, 0)).collect();
+
+    for ballot in ballots {
+        let ranking = restricted_ranking(ballot, &set);
+        let count = ballot.count as u64;
+        let ranked = ranking.len() as u64;
+
+        for (position, candidate) in ranking.iter().enumerate() {
+            let points = match truncation {
+                BordaTruncation::Pessimistic | BordaTruncation::Averaged => n - 1 - position as u64,
+                BordaTruncation::Modified => ranked - position as u64,
+            };
+            *scores.get_mut(candidate).unwrap() += 2 * points * count;
+        }
+
+        if truncation == BordaTruncation::Averaged && ranked < n {
+            // The unranked candidates share the points 0 to (n - ranked - 1),
+            // whose average doubled is (n - ranked - 1).
+            let points = n - ranked - 1;
+            for candidate in candidates.iter().filter(|c| !ranking.contains(c)) {
+                *scores.get_mut(candidate).unwrap() += points * count;
+            }
+        }
+    }
+
+    scores
+}
+
+/// Return the configured rule for scoring truncated ballots.
+pub fn truncation(options: &TabulationOptions) -> BordaTruncation {
+    options
+        .borda_truncation
+        .unwrap_or(BordaTruncation::Pessimistic)
+}
+
+/// Rank candidates by their Borda score, highest first.
+pub fn borda(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+) -> MethodResult {
+    let scores = borda_scores(candidates, ballots, truncation(options));
+    rank_by_key(candidates, |c| Reverse(scores[&c]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::tests::ballots;
+
+    #[test]
+    fn test_truncation() {
+        let candidates = [CandidateId(0), CandidateId(1), CandidateId(2)];
+        let ballots = ballots(&[(1, &[0])]);
+        let scores = |truncation| {
+            let scores = borda_scores(&candidates, &ballots, truncation);
+            candidates.iter().map(|c| scores[c]).collect::<Vec<u64>>()
+        };
+
+        assert_eq!(vec![4, 0, 0], scores(BordaTruncation::Pessimistic));
+        assert_eq!(vec![4, 1, 1], scores(BordaTruncation::Averaged));
+        assert_eq!(vec![2, 0, 0], scores(BordaTruncation::Modified));
+    }
+}
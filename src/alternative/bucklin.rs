@@ -0,0 +1,50 @@
+use crate::alternative::restricted_ranking;
+use crate::condorcet::rank_by_key;
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::MethodResult;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Rank candidates by the Bucklin method: count each candidate's votes in
+/// the top one, two, three... ranks of each ballot until a candidate has a
+/// majority. Candidates are placed by how deep the count went before they
+/// had a majority, then by their votes at that depth. Candidates who never
+/// have a majority come last, by their total votes.
+pub fn bucklin(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    _options: &TabulationOptions,
+) -> MethodResult {
+    let set: HashSet<CandidateId> = candidates.iter().copied().collect();
+    let rankings: Vec<(Vec<CandidateId>, u64)> = ballots
+        .iter()
+        .map(|ballot| (restricted_ranking(ballot, &set), ballot.count as u64))
+        .filter(|(ranking, _)| !ranking.is_empty())
+        .collect();
+    let voters: u64 = rankings.iter().map(|(_, count)| count).sum();
+
+    let mut votes: HashMap<CandidateId, u64> = candidates.iter().map(|c| (*c, 0)).collect();
+    let mut majority_at: HashMap<CandidateId, (usize, Reverse<u64>)> = HashMap::new();
+
+    for depth in 0..candidates.len() {
+        for (ranking, count) in &rankings {
+            if let Some(c) = ranking.get(depth) {
+                *votes.get_mut(c).unwrap() += count;
+            }
+        }
+
+        for c in candidates {
+            if !majority_at.contains_key(c) && votes[c] * 2 > voters {
+                majority_at.insert(*c, (depth, Reverse(votes[c])));
+            }
+        }
+    }
+
+    rank_by_key(candidates, |c| {
+        majority_at
+            .get(&c)
+            .copied()
+            .unwrap_or((candidates.len(), Reverse(votes[&c])))
+    })
+}
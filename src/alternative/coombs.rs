@@ -0,0 +1,71 @@
+use crate::alternative::restricted_ranking;
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::MethodResult;
+use std::collections::{HashMap, HashSet};
+
+/// Rank candidates by Coombs' method: a candidate with a majority of first
+/// choices among the remaining candidates is placed next; otherwise the
+/// candidates ranked last by the most ballots are eliminated.
+///
+/// A ballot ranks a candidate last if it ranks every other remaining
+/// candidate above them. Ballots that leave two or more remaining
+/// candidates unranked don't rank anyone last.
+pub fn coombs(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    _options: &TabulationOptions,
+) -> MethodResult {
+    let mut remaining = candidates.to_vec();
+    let mut placed: Vec<Vec<CandidateId>> = Vec::new();
+    let mut eliminated: Vec<Vec<CandidateId>> = Vec::new();
+
+    while !remaining.is_empty() {
+        let set: HashSet<CandidateId> = remaining.iter().copied().collect();
+        let mut first: HashMap<CandidateId, u64> = HashMap::new();
+        let mut last: HashMap<CandidateId, u64> = HashMap::new();
+        let mut continuing: u64 = 0;
+
+        for ballot in ballots {
+            let ranking = restricted_ranking(ballot, &set);
+            let count = ballot.count as u64;
+
+            if let Some(c) = ranking.first() {
+                *first.entry(*c).or_default() += count;
+                continuing += count;
+            }
+
+            let last_place = match remaining.len() - ranking.len() {
+                0 => ranking.last().copied(),
+                1 => remaining.iter().find(|c| !ranking.contains(c)).copied(),
+                _ => None,
+            };
+            if let Some(c) = last_place {
+                *last.entry(c).or_default() += count;
+            }
+        }
+
+        let majority = remaining
+            .iter()
+            .find(|c| first.get(c).copied().unwrap_or(0) * 2 > continuing)
+            .copied();
+
+        if let Some(winner) = majority {
+            placed.push(vec![winner]);
+            remaining.retain(|c| *c != winner);
+        } else {
+            let last_votes = |c: &CandidateId| last.get(c).copied().unwrap_or(0);
+            let most = remaining.iter().map(last_votes).max().unwrap();
+            let losers: Vec<CandidateId> = remaining
+                .iter()
+                .filter(|c| last_votes(c) == most)
+                .copied()
+                .collect();
+            remaining.retain(|c| !losers.contains(c));
+            eliminated.push(losers);
+        }
+    }
+
+    placed.extend(eliminated.into_iter().rev());
+    MethodResult::from_ranking(placed)
+}
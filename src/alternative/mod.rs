@@ -0,0 +1,118 @@
+mod baldwin;
+mod borda;
+mod bucklin;
+mod coombs;
+mod nanson;
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::MethodResult;
+use std::collections::{BTreeMap, HashSet};
+
+/// A ranked-ballot method that ranks `candidates` from the given ballots.
+type Method = fn(&[CandidateId], &[NormalizedBallot], &TabulationOptions) -> MethodResult;
+
+/// Methods reported alongside the official tabulation, by the name they are
+/// reported under.
+const METHODS: &[(&str, Method)] = &[
+    ("borda", borda::borda),
+    ("coombs", coombs::coombs),
+    ("bucklin", bucklin::bucklin),
+    ("baldwin", baldwin::baldwin),
+    ("nanson", nanson::nanson),
+];
+
+/// Run every alternative method on a contest.
+pub fn alternative_methods(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+) -> BTreeMap<String, MethodResult> {
+    METHODS
+        .iter()
+        .map(|(name, method)| (name.to_string(), method(candidates, ballots, options)))
+        .collect()
+}
+
+/// Return the candidates of `ballot` that are in `candidates`, in the order
+/// the ballot ranks them. An overvoted ranking counts for its only candidate
+/// in `candidates`, and ends the ranking if it has more than one.
+fn restricted_ranking(
+    ballot: &NormalizedBallot,
+    candidates: &HashSet<CandidateId>,
+) -> Vec<CandidateId> {
+    ballot.ranking_among(|c| candidates.contains(&c))
+}
+
+/// Rank candidates by repeatedly removing the candidates that `eliminate`
+/// picks from those remaining, which must be at least one of them.
+/// Candidates removed together are tied, and the last removed come first.
+fn rank_by_elimination(
+    candidates: &[CandidateId],
+    eliminate: impl Fn(&[CandidateId]) -> Vec<CandidateId>,
+) -> MethodResult {
+    let mut remaining = candidates.to_vec();
+    let mut ranking = Vec::new();
+
+    while !remaining.is_empty() {
+        let eliminated = eliminate(&remaining);
+        remaining.retain(|c| !eliminated.contains(c));
+        ranking.push(eliminated);
+    }
+
+    ranking.reverse();
+    MethodResult::from_ranking(ranking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::tests::ballots;
+
+    const MEMPHIS: CandidateId = CandidateId(0);
+    const NASHVILLE: CandidateId = CandidateId(1);
+    const CHATTANOOGA: CandidateId = CandidateId(2);
+    const KNOXVILLE: CandidateId = CandidateId(3);
+
+    /// The Tennessee capital example, in which Memphis has a plurality but
+    /// Nashville is the Condorcet winner.
+    fn tennessee() -> Vec<NormalizedBallot> {
+        ballots(&[
+            (42, &[0, 1, 2, 3]),
+            (26, &[1, 2, 3, 0]),
+            (15, &[2, 3, 1, 0]),
+            (17, &[3, 2, 1, 0]),
+        ])
+    }
+
+    fn ranking(method: &str) -> Vec<Vec<CandidateId>> {
+        let candidates = [MEMPHIS, NASHVILLE, CHATTANOOGA, KNOXVILLE];
+        let results = alternative_methods(&candidates, &tennessee(), &Default::default());
+        let result = &results[method];
+        assert_eq!(Some(NASHVILLE), result.winner);
+        result.ranking.clone()
+    }
+
+    #[test]
+    fn test_tennessee() {
+        let n = vec![NASHVILLE];
+        let c = vec![CHATTANOOGA];
+        let m = vec![MEMPHIS];
+        let k = vec![KNOXVILLE];
+
+        assert_eq!(
+            vec![n.clone(), c.clone(), m.clone(), k.clone()],
+            ranking("borda")
+        );
+        assert_eq!(
+            vec![n.clone(), c.clone(), k.clone(), m.clone()],
+            ranking("coombs")
+        );
+        assert_eq!(
+            vec![n.clone(), c.clone(), k.clone(), m.clone()],
+            ranking("bucklin")
+        );
+        assert_eq!(vec![n.clone(), c.clone(), m, k], ranking("baldwin"));
+        assert_eq!(vec![n, c, vec![MEMPHIS, KNOXVILLE]], ranking("nanson"));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::alternative::borda::{borda_scores, truncation};
+use crate::alternative::rank_by_elimination;
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::model::report::MethodResult;
+
+/// Rank candidates by Nanson's method: repeatedly recount Borda scores among
+/// the remaining candidates and eliminate every candidate scoring below the
+/// average.
+pub fn nanson(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    options: &TabulationOptions,
+) -> MethodResult {
+    rank_by_elimination(candidates, |remaining| {
+        let scores = borda_scores(remaining, ballots, truncation(options));
+        let total: u64 = scores.values().sum();
+        let n = remaining.len() as u64;
+
+        let below_average: Vec<CandidateId> = remaining
+            .iter()
+            .filter(|c| scores[c] * n < total)
+            .copied()
+            .collect();
+
+        if below_average.is_empty() {
+            // Every candidate has the same score.
+            remaining.to_vec()
+        } else {
+            below_average
+        }
+    })
+}
@@ -0,0 +1,5 @@
+mod raire;
+mod simulate;
+
+pub use raire::raire_assertions;
+pub use simulate::{get_audit_kind, simulate_audit, AuditParameters};
@@ -0,0 +1,368 @@
+//! RAIRE assertions for single-seat IRV contests, as described in Blom,
+//! Stuckey and Teague, "RAIRE: Risk-Limiting Audits for IRV Elections" (2019).
+//!
+//! Every outcome in which another candidate wins has an elimination order
+//! ending in that candidate. The search looks at the last few candidates of
+//! such orders (the "tail") and finds, for each, the assertion with the
+//! widest margin that rules out every order ending that way. It only looks
+//! further back into an order when that would avoid a narrower assertion
+//! than it already needs.
+
+use crate::model::audit::{Assertion, AssertionKind, ContestAssertions};
+use crate::model::election::{first_continuing, CandidateId, NormalizedBallot};
+use crate::tabulator::TabulatorRound;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashMap};
+
+/// Most tails to expand before giving up. The search is exponential in the
+/// number of candidates in the worst case.
+const MAX_EXPANSIONS: usize = 100_000;
+
+/// An assertion before its margin is diluted. Candidates are given as
+/// positions in the list of contest candidates.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Claim {
+    kind: AssertionKind,
+    winner: usize,
+    loser: usize,
+    continuing: Vec<usize>,
+    margin: u32,
+}
+
+/// A ballot's rankings, each the candidates marked at it, with the number of
+/// ballots like it.
+type Rankings = (Vec<Vec<usize>>, u32);
+
+struct Tallies {
+    rankings: Vec<Rankings>,
+    /// Number of ballots whose first ranking is of only that candidate: the
+    /// fewest votes the candidate can have while they continue.
+    first: Vec<u32>,
+    /// `ahead[a][b]` is the number of ballots that mark `a` at a ranking
+    /// above every ranking that marks `b`: the most votes `a` can have while
+    /// `b` continues.
+    ahead: Vec<Vec<u32>>,
+    /// Votes of each candidate, by the sorted set of continuing candidates.
+    contexts: HashMap<Vec<usize>, Vec<u32>>,
+}
+
+impl Tallies {
+    fn new(rankings: Vec<Rankings>, n: usize) -> Tallies {
+        let mut first = vec![0; n];
+        let mut ahead = vec![vec![0; n]; n];
+        for (ranking, count) in &rankings {
+            if let Some([c]) = ranking.first().map(|r| r.as_slice()) {
+                first[*c] += count;
+            }
+            let mut marked: Vec<usize> = Vec::new();
+            for candidates in ranking {
+                let above = marked.len();
+                for c in candidates {
+                    if !marked.contains(c) {
+                        marked.push(*c);
+                    }
+                }
+                for a in &marked[above..] {
+                    for b in (0..n).filter(|b| !marked.contains(b)) {
+                        ahead[*a][b] += count;
+                    }
+                }
+            }
+        }
+
+        Tallies {
+            rankings,
+            first,
+            ahead,
+            contexts: HashMap::new(),
+        }
+    }
+
+    fn winner_only(&self, winner: usize, loser: usize) -> Option<Claim> {
+        let margin = self.first[winner].checked_sub(self.ahead[loser][winner])?;
+        if margin == 0 {
+            return None;
+        }
+        Some(Claim {
+            kind: AssertionKind::WinnerOnly,
+            winner,
+            loser,
+            continuing: Vec::new(),
+            margin,
+        })
+    }
+
+    fn irv_elimination(
+        &mut self,
+        winner: usize,
+        loser: usize,
+        continuing: &[usize],
+    ) -> Option<Claim> {
+        let mut continuing = continuing.to_vec();
+        continuing.sort_unstable();
+
+        let rankings = &self.rankings;
+        let n = self.first.len();
+        let votes = self.contexts.entry(continuing.clone()).or_insert_with(|| {
+            let mut votes = vec![0; n];
+            for (ranking, count) in rankings {
+                if let Some(c) = first_continuing(ranking, |c| continuing.contains(&c)) {
+                    votes[c] += count;
+                }
+            }
+            votes
+        });
+
+        let margin = votes[winner].checked_sub(votes[loser])?;
+        if margin == 0 {
+            return None;
+        }
+        Some(Claim {
+            kind: AssertionKind::IrvElimination,
+            winner,
+            loser,
+            continuing,
+            margin,
+        })
+    }
+
+    /// Find the assertion with the widest margin that rules out every
+    /// elimination order ending in `tail`.
+    fn best_claim(&mut self, tail: &[usize]) -> Option<Claim> {
+        let n = self.first.len();
+        let mut claims: Vec<Claim> = Vec::new();
+
+        // Each candidate in the tail outlasts every candidate before them,
+        // including those not in the tail.
+        for (i, later) in tail.iter().enumerate() {
+            let earlier = tail[..i]
+                .iter()
+                .copied()
+                .chain((0..n).filter(|c| !tail.contains(c)));
+            claims.extend(earlier.filter_map(|c| self.winner_only(c, *later)));
+        }
+
+        // The first candidate of the tail is eliminated while the whole tail
+        // continues.
+        for loser in tail.iter().skip(1) {
+            claims.extend(self.irv_elimination(tail[0], *loser, tail));
+        }
+
+        claims.into_iter().rev().max_by_key(|c| c.margin)
+    }
+}
+
+/// Generate RAIRE assertions confirming the winner of a single-seat IRV
+/// contest, given its ballots and the rounds of its tabulation. Returns
+/// `None` if no set of assertions can rule out every other winner, as in a
+/// contest decided by a tie.
+pub fn raire_assertions(
+    ballots: &[NormalizedBallot],
+    rounds: &[TabulatorRound],
+) -> Option<ContestAssertions> {
+    let mut candidates: Vec<CandidateId> = rounds[0]
+        .allocations
+        .iter()
+        .filter(|a| a.votes > 0)
+        .flat_map(|a| a.allocatee.candidate_id())
+        .collect();
+    candidates.sort();
+    let winner_id = *rounds.iter().flat_map(|r| &r.elected).next()?;
+    let winner = candidates.iter().position(|c| *c == winner_id)?;
+    let n = candidates.len();
+    if n < 2 {
+        return None;
+    }
+
+    // Candidates outside the contest are eliminated before any assertion
+    // applies, so rankings of only them are passed over.
+    let rankings: Vec<Rankings> = ballots
+        .iter()
+        .map(|b| {
+            let ranking = b
+                .rankings()
+                .map(|r| {
+                    r.candidates()
+                        .iter()
+                        .flat_map(|c| candidates.iter().position(|d| d == c))
+                        .collect::<Vec<usize>>()
+                })
+                .filter(|r| !r.is_empty())
+                .collect();
+            (ranking, b.count)
+        })
+        .collect();
+    let total_ballots: u32 = rankings.iter().map(|(_, count)| count).sum();
+    let mut tallies = Tallies::new(rankings, n);
+
+    // Tails are taken in order of their assertion's margin, narrowest first,
+    // with tails no assertion rules out taken before all others.
+    let mut frontier: BinaryHeap<(Reverse<u32>, Vec<usize>, Option<Claim>)> = BinaryHeap::new();
+    for c in (0..n).filter(|c| *c != winner) {
+        let claim = tallies.best_claim(&[c]);
+        frontier.push((
+            Reverse(claim.as_ref().map_or(0, |c| c.margin)),
+            vec![c],
+            claim,
+        ));
+    }
+
+    // The narrowest margin the assertions found so far need.
+    let mut needed: Option<u32> = None;
+    let mut claims: BTreeSet<Claim> = BTreeSet::new();
+    let mut expansions = 0;
+
+    while let Some((Reverse(margin), tail, claim)) = frontier.pop() {
+        if needed.is_some_and(|needed| margin >= needed) {
+            claims.insert(claim.unwrap());
+            continue;
+        }
+
+        if tail.len() == n {
+            // The whole order is known, so there is no looking further back.
+            needed = Some(margin);
+            claims.insert(claim?);
+            continue;
+        }
+
+        expansions += 1;
+        if expansions > MAX_EXPANSIONS {
+            return None;
+        }
+
+        for c in (0..n).filter(|c| !tail.contains(c)) {
+            let mut child = vec![c];
+            child.extend(&tail);
+            // An assertion that rules out a tail rules out any longer tail
+            // ending the same way.
+            let child_claim = match (tallies.best_claim(&child), &claim) {
+                (Some(own), Some(parent)) if own.margin > parent.margin => Some(own),
+                (own, None) => own,
+                (_, parent) => parent.clone(),
+            };
+            let child_margin = child_claim.as_ref().map_or(0, |c| c.margin);
+            frontier.push((Reverse(child_margin), child, child_claim));
+        }
+    }
+
+    let needed = needed?;
+    let assertions = claims
+        .into_iter()
+        .map(|claim| Assertion {
+            kind: claim.kind,
+            winner: candidates[claim.winner],
+            loser: candidates[claim.loser],
+            continuing: claim.continuing.iter().map(|c| candidates[*c]).collect(),
+            margin: claim.margin,
+            diluted_margin: claim.margin as f64 / total_ballots as f64,
+        })
+        .collect();
+
+    Some(ContestAssertions {
+        winner: winner_id,
+        candidates,
+        total_ballots,
+        difficulty: total_ballots as f64 / needed as f64,
+        assertions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::metadata::TabulationOptions;
+    use crate::tabulator::tabulate;
+    use crate::tabulator::tests::{ballots, candidates};
+    use crate::util::Rng;
+    use itertools::Itertools;
+
+    fn assertions(spec: &[(u32, &[u32])], n: u32) -> Option<ContestAssertions> {
+        let ballots = ballots(spec);
+        let rounds = tabulate(&ballots, &candidates(n), &TabulationOptions::default());
+        raire_assertions(&ballots, &rounds)
+    }
+
+    /// Return true if the assertion shows that candidates can't be
+    /// eliminated in the given order.
+    fn rules_out(assertion: &Assertion, order: &[CandidateId]) -> bool {
+        let position = |c: CandidateId| order.iter().position(|d| *d == c).unwrap();
+        match assertion.kind {
+            AssertionKind::WinnerOnly => position(assertion.winner) < position(assertion.loser),
+            AssertionKind::IrvElimination => (0..order.len()).any(|i| {
+                let mut continuing = order[i..].to_vec();
+                continuing.sort();
+                order[i] == assertion.winner && continuing == assertion.continuing
+            }),
+        }
+    }
+
+    #[test]
+    fn test_two_candidates() {
+        let assertions = assertions(&[(60, &[0]), (40, &[1])], 2).unwrap();
+
+        assert_eq!(CandidateId(0), assertions.winner);
+        assert_eq!(1, assertions.assertions.len());
+        let assertion = &assertions.assertions[0];
+        assert_eq!(AssertionKind::WinnerOnly, assertion.kind);
+        assert_eq!(CandidateId(1), assertion.loser);
+        assert_eq!(20, assertion.margin);
+        assert_eq!(0.2, assertion.diluted_margin);
+        assert_eq!(5., assertions.difficulty);
+    }
+
+    #[test]
+    fn test_elimination_order() {
+        // C is eliminated first and their votes elect B, 60 to 40. A can only
+        // win if C outlasts B, and C can only win if they outlast both.
+        let assertions = assertions(&[(40, &[0]), (35, &[1]), (25, &[2, 1])], 3).unwrap();
+        let found: Vec<(AssertionKind, u32, u32, usize, u32)> = assertions
+            .assertions
+            .iter()
+            .map(|a| (a.kind, a.winner.0, a.loser.0, a.continuing.len(), a.margin))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (AssertionKind::WinnerOnly, 0, 2, 0, 15),
+                (AssertionKind::WinnerOnly, 1, 2, 0, 10),
+                (AssertionKind::IrvElimination, 1, 0, 2, 20),
+            ],
+            found
+        );
+        assert_eq!(10., assertions.difficulty);
+    }
+
+    #[test]
+    fn test_tie() {
+        assert_eq!(None, assertions(&[(50, &[0]), (50, &[1])], 2));
+    }
+
+    #[test]
+    fn test_rules_out_other_winners() {
+        let mut rng = Rng::new(19);
+        let candidates: Vec<CandidateId> = (0..4).map(CandidateId).collect();
+        let orders: Vec<Vec<u32>> = (1..=4).flat_map(|k| (0..4).permutations(k)).collect();
+
+        let mut tested = 0;
+        while tested < 20 {
+            let spec: Vec<(u32, Vec<u32>)> = (0..8)
+                .map(|_| {
+                    let order = &orders[rng.below(orders.len() as u64) as usize];
+                    (rng.below(20) as u32 + 1, order.clone())
+                })
+                .collect();
+            let spec: Vec<(u32, &[u32])> = spec.iter().map(|(n, r)| (*n, &r[..])).collect();
+            let assertions = match assertions(&spec, 4) {
+                Some(assertions) if assertions.candidates == candidates => assertions,
+                _ => continue,
+            };
+
+            for order in candidates.iter().copied().permutations(4) {
+                if *order.last().unwrap() != assertions.winner {
+                    assert!(assertions.assertions.iter().any(|a| rules_out(a, &order)));
+                }
+            }
+            tested += 1;
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Simulation of risk-limiting audits of RAIRE assertions, to estimate how
+//! many ballots an audit would need to sample.
+//!
+//! Ballot-polling audits use BRAVO for each assertion, drawing ballots from
+//! the reported ballots, so they assume the reported outcome is right.
+//! Comparison audits use the Kaplan-Markov bound, with each sampled ballot's
+//! record overstating every assertion's margin by one vote at a given rate.
+
+use crate::model::audit::{Assertion, AssertionKind, ContestAssertions};
+use crate::model::election::{first_continuing, CandidateId, NormalizedBallot};
+use crate::util::Rng;
+
+/// Error inflation factor of comparison audits.
+const GAMMA: f64 = 1.1;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AuditKind {
+    BallotPolling,
+    Comparison,
+}
+
+pub fn get_audit_kind(kind: &str) -> AuditKind {
+    match kind {
+        "ballotPolling" => AuditKind::BallotPolling,
+        "comparison" => AuditKind::Comparison,
+        _ => panic!("The audit kind {} is not implemented.", kind),
+    }
+}
+
+pub struct AuditParameters {
+    pub kind: AuditKind,
+    /// Largest chance the audit may have of confirming a wrong outcome.
+    pub risk_limit: f64,
+    /// Share of sampled ballots whose record overstates the margins by one
+    /// vote, for comparison audits.
+    pub error_rate: f64,
+    /// Number of audits to simulate.
+    pub trials: u32,
+}
+
+/// How much a sampled ballot multiplies the evidence for an assertion.
+struct Factors {
+    winner: f64,
+    loser: f64,
+    neither: f64,
+    /// For a ballot whose record overstates the margin by one vote.
+    error: f64,
+}
+
+pub struct SampleSizes {
+    /// Ballots each simulated audit sampled, in increasing order. An audit
+    /// that doesn't confirm the outcome samples every ballot.
+    pub trials: Vec<u32>,
+    /// Mean number of ballots sampled to confirm each assertion.
+    pub assertions: Vec<f64>,
+}
+
+impl SampleSizes {
+    pub fn mean(&self) -> f64 {
+        self.trials.iter().map(|n| *n as f64).sum::<f64>() / self.trials.len() as f64
+    }
+
+    /// Sample size that the given share of simulated audits didn't exceed.
+    pub fn quantile(&self, q: f64) -> u32 {
+        let i = (q * self.trials.len() as f64).ceil() as usize;
+        self.trials[i.clamp(1, self.trials.len()) - 1]
+    }
+}
+
+/// Whether a ballot counts for the assertion's winner (`Some(true)`), its
+/// loser (`Some(false)`), or neither, given the candidates marked at each of
+/// its rankings.
+fn vote(assertion: &Assertion, ranking: &[Vec<CandidateId>]) -> Option<bool> {
+    match assertion.kind {
+        AssertionKind::WinnerOnly => {
+            if ranking.first() == Some(&vec![assertion.winner]) {
+                Some(true)
+            } else {
+                ranking
+                    .iter()
+                    .find(|r| r.contains(&assertion.winner) || r.contains(&assertion.loser))
+                    .filter(|r| !r.contains(&assertion.winner))
+                    .map(|_| false)
+            }
+        }
+        AssertionKind::IrvElimination => {
+            first_continuing(ranking, |c| assertion.continuing.contains(&c))
+                .filter(|c| *c == assertion.winner || *c == assertion.loser)
+                .map(|c| c == assertion.winner)
+        }
+    }
+}
+
+/// Simulate audits of the given assertions, returning the number of ballots
+/// each one sampled.
+pub fn simulate_audit(
+    ballots: &[NormalizedBallot],
+    assertions: &ContestAssertions,
+    parameters: &AuditParameters,
+    rng: &mut Rng,
+) -> SampleSizes {
+    // Cumulative ballot counts, to draw ballots in proportion to their count.
+    let mut cumulative: Vec<u32> = Vec::with_capacity(ballots.len());
+    let mut votes: Vec<Vec<Option<bool>>> = Vec::with_capacity(ballots.len());
+    let mut total = 0;
+    for ballot in ballots {
+        let ranking: Vec<Vec<CandidateId>> = ballot
+            .rankings()
+            .map(|r| {
+                r.candidates()
+                    .iter()
+                    .copied()
+                    .filter(|c| assertions.candidates.contains(c))
+                    .collect::<Vec<CandidateId>>()
+            })
+            .filter(|r| !r.is_empty())
+            .collect();
+        votes.push(
+            assertions
+                .assertions
+                .iter()
+                .map(|a| vote(a, &ranking))
+                .collect(),
+        );
+        total += ballot.count;
+        cumulative.push(total);
+    }
+
+    let factors: Vec<Factors> = (0..assertions.assertions.len())
+        .map(|a| match parameters.kind {
+            AuditKind::BallotPolling => {
+                let count = |side: bool| -> f64 {
+                    votes
+                        .iter()
+                        .zip(ballots)
+                        .filter(|(v, _)| v[a] == Some(side))
+                        .map(|(_, b)| b.count as f64)
+                        .sum()
+                };
+                let share = count(true) / (count(true) + count(false));
+                Factors {
+                    winner: 2. * share,
+                    loser: 2. * (1. - share),
+                    neither: 1.,
+                    error: 1.,
+                }
+            }
+            AuditKind::Comparison => {
+                let margin = assertions.assertions[a].diluted_margin;
+                let factor = 1. / (1. - margin / (2. * GAMMA));
+                Factors {
+                    winner: factor,
+                    loser: factor,
+                    neither: factor,
+                    error: factor * (1. - 1. / (2. * GAMMA)),
+                }
+            }
+        })
+        .collect();
+
+    let threshold = 1. / parameters.risk_limit;
+    let mut trials = Vec::new();
+    let mut sums = vec![0.; factors.len()];
+
+    for _ in 0..parameters.trials {
+        let mut evidence = vec![1.; factors.len()];
+        let mut confirmed: Vec<Option<u32>> = vec![None; factors.len()];
+        let mut sampled = 0;
+
+        while sampled < total && confirmed.iter().any(|c| c.is_none()) {
+            sampled += 1;
+            let draw = rng.below(total as u64) as u32;
+            let ballot = cumulative.partition_point(|c| *c <= draw);
+            let error =
+                parameters.kind == AuditKind::Comparison && rng.next_f64() < parameters.error_rate;
+
+            for (a, factors) in factors.iter().enumerate() {
+                if confirmed[a].is_some() {
+                    continue;
+                }
+                evidence[a] *= match (error, votes[ballot][a]) {
+                    (true, _) => factors.error,
+                    (false, Some(true)) => factors.winner,
+                    (false, Some(false)) => factors.loser,
+                    (false, None) => factors.neither,
+                };
+                if evidence[a] >= threshold {
+                    confirmed[a] = Some(sampled);
+                }
+            }
+        }
+
+        for (sum, c) in sums.iter_mut().zip(&confirmed) {
+            *sum += c.unwrap_or(total) as f64;
+        }
+        trials.push(sampled);
+    }
+
+    trials.sort_unstable();
+    SampleSizes {
+        trials,
+        assertions: sums
+            .into_iter()
+            .map(|s| s / parameters.trials as f64)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::raire_assertions;
+    use crate::model::metadata::TabulationOptions;
+    use crate::tabulator::tabulate;
+    use crate::tabulator::tests::{ballots, candidates};
+
+    fn simulate(spec: &[(u32, &[u32])], kind: AuditKind, error_rate: f64) -> SampleSizes {
+        let ballots = ballots(spec);
+        let rounds = tabulate(&ballots, &candidates(3), &TabulationOptions::default());
+        let assertions = raire_assertions(&ballots, &rounds).unwrap();
+        let parameters = AuditParameters {
+            kind,
+            risk_limit: 0.05,
+            error_rate,
+            trials: 50,
+        };
+        simulate_audit(&ballots, &assertions, &parameters, &mut Rng::new(20))
+    }
+
+    #[test]
+    fn test_comparison() {
+        // The diluted margin is 0.2, and each ballot multiplies the evidence
+        // by 1 / (1 - 0.2 / 2.2). It takes 32 ballots to pass 1 / 0.05.
+        let sizes = simulate(&[(600, &[0]), (400, &[1])], AuditKind::Comparison, 0.);
+        assert_eq!(32, sizes.quantile(0.));
+        assert_eq!(32, sizes.quantile(1.));
+
+        let with_errors = simulate(&[(600, &[0]), (400, &[1])], AuditKind::Comparison, 0.05);
+        assert!(with_errors.mean() > 32.);
+    }
+
+    #[test]
+    fn test_ballot_polling() {
+        let wide = simulate(&[(700, &[0]), (300, &[1])], AuditKind::BallotPolling, 0.);
+        let narrow = simulate(
+            &[(550, &[0]), (400, &[1]), (50, &[2, 1])],
+            AuditKind::BallotPolling,
+            0.,
+        );
+
+        assert!(wide.mean() < narrow.mean());
+        assert!(narrow.quantile(0.5) <= narrow.quantile(0.9));
+        assert!(narrow.quantile(1.) <= 1000);
+    }
+
+    #[test]
+    fn test_vote() {
+        let assertion = Assertion {
+            kind: AssertionKind::WinnerOnly,
+            winner: CandidateId(0),
+            loser: CandidateId(1),
+            continuing: Vec::new(),
+            margin: 1,
+            diluted_margin: 0.1,
+        };
+        let ranking = |ids: &[u32]| -> Vec<Vec<CandidateId>> {
+            ids.iter().map(|i| vec![CandidateId(*i)]).collect()
+        };
+
+        assert_eq!(Some(true), vote(&assertion, &ranking(&[0, 1])));
+        assert_eq!(Some(false), vote(&assertion, &ranking(&[2, 1, 0])));
+        assert_eq!(None, vote(&assertion, &ranking(&[2, 0, 1])));
+        // An overvote of the winner and loser counts for neither.
+        let overvoted = vec![vec![CandidateId(0), CandidateId(1)]];
+        assert_eq!(None, vote(&assertion, &overvoted));
+
+        let assertion = Assertion {
+            kind: AssertionKind::IrvElimination,
+            continuing: vec![CandidateId(0), CandidateId(1)],
+            ..assertion
+        };
+        assert_eq!(Some(true), vote(&assertion, &ranking(&[2, 0, 1])));
+        assert_eq!(None, vote(&assertion, &ranking(&[2])));
+        // Candidate 2 isn't continuing, so the overvote counts for the loser.
+        let overvoted = vec![vec![CandidateId(1), CandidateId(2)], vec![CandidateId(0)]];
+        assert_eq!(Some(false), vote(&assertion, &overvoted));
+    }
+}
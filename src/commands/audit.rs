@@ -0,0 +1,66 @@
+use crate::audit::{get_audit_kind, raire_assertions, simulate_audit, AuditParameters};
+use crate::model::election::{CandidateId, ElectionPreprocessed};
+use crate::tabulator::get_tabulation_method;
+use crate::util::{read_serialized, Rng};
+use colored::*;
+use std::path::Path;
+
+pub fn audit(
+    preprocessed_file: &Path,
+    kind: &str,
+    risk_limit: f64,
+    error_rate: f64,
+    trials: u32,
+    seed: u64,
+) {
+    let preprocessed: ElectionPreprocessed = read_serialized(preprocessed_file);
+    let options = &preprocessed.info.tabulation_options;
+    if !options.is_standard_irv() {
+        panic!(
+            "Audits can only be simulated for single-seat IRV contests with single or batch elimination."
+        );
+    }
+
+    let ballots = preprocessed.ballots.grouped_ballots();
+    let rounds = get_tabulation_method("irv").tabulate(
+        &preprocessed.ballots.candidates,
+        &preprocessed.ballots.ballots,
+        &ballots,
+        options,
+    );
+    let assertions = match raire_assertions(&ballots, &rounds[0]) {
+        Some(assertions) => assertions,
+        None => {
+            eprintln!(
+                "{}",
+                "No audit assertions found; the contest needs a full hand count.".purple()
+            );
+            return;
+        }
+    };
+
+    let parameters = AuditParameters {
+        kind: get_audit_kind(kind),
+        risk_limit,
+        error_rate,
+        trials,
+    };
+    let sizes = simulate_audit(&ballots, &assertions, &parameters, &mut Rng::new(seed));
+
+    let candidates = &preprocessed.ballots.candidates;
+    let name = |c: CandidateId| &candidates[c.0 as usize].name;
+    eprintln!("Ballots: {}", assertions.total_ballots.to_string().blue());
+    for (assertion, size) in assertions.assertions.iter().zip(&sizes.assertions) {
+        eprintln!(
+            "Assertion: {:?} {} > {}, diluted margin {:.4}, mean sample {}",
+            assertion.kind,
+            name(assertion.winner),
+            name(assertion.loser),
+            assertion.diluted_margin,
+            format!("{:.1}", size).blue()
+        );
+    }
+    eprintln!("Mean sample: {}", format!("{:.1}", sizes.mean()).blue());
+    eprintln!("Median sample: {}", sizes.quantile(0.5).to_string().blue());
+    eprintln!("90% of samples: {}", sizes.quantile(0.9).to_string().blue());
+}
@@ -1,7 +1,9 @@
+mod audit;
 mod info;
 mod report;
 mod sync;
 
+pub use audit::audit;
 pub use info::info;
 pub use report::report;
 pub use sync::sync;
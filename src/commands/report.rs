@@ -1,3 +1,4 @@
+use crate::audit::raire_assertions;
 use crate::model::election::ElectionPreprocessed;
 use crate::model::report::{ContestIndexEntry, ElectionIndexEntry, ReportIndex};
 use crate::read_metadata::read_meta;
@@ -14,7 +15,11 @@ pub fn report(
     preprocessed_dir: &Path,
     force_preprocess: bool,
     force_report: bool,
+    normalization_trail: bool,
 ) {
+    // The trail is only recorded while normalizing, so cached preprocessed
+    // files have to be regenerated to write it.
+    let force_preprocess = force_preprocess || normalization_trail;
     let raw_path = Path::new(raw_dir);
     let mut election_index_entries: Vec<ElectionIndexEntry> = Vec::new();
 
@@ -35,12 +40,12 @@ pub fn report(
 
                 let report_path = Path::new(report_dir)
                     .join(&jurisdiction.path)
-                    .join(&election_path)
+                    .join(election_path)
                     .join(&contest.office)
                     .join("report.json");
                 let preprocessed_path = Path::new(preprocessed_dir)
                     .join(&jurisdiction.path)
-                    .join(&election_path)
+                    .join(election_path)
                     .join(&contest.office)
                     .join("normalized.json.gz");
 
@@ -55,37 +60,64 @@ pub fn report(
                     );
                     read_serialized(&report_path)
                 } else {
-                    create_dir_all(&report_path.parent().unwrap()).unwrap();
+                    create_dir_all(report_path.parent().unwrap()).unwrap();
 
-                    let preprocessed: ElectionPreprocessed =
-                        if preprocessed_path.exists() && !force_preprocess {
-                            eprintln!(
-                                "Loading preprocessed {}.",
-                                preprocessed_path.to_str().unwrap().bright_cyan()
-                            );
-                            read_serialized(&preprocessed_path)
-                        } else {
-                            create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
+                    let preprocessed: ElectionPreprocessed = if preprocessed_path.exists()
+                        && !force_preprocess
+                    {
+                        eprintln!(
+                            "Loading preprocessed {}.",
+                            preprocessed_path.to_str().unwrap().bright_cyan()
+                        );
+                        read_serialized(&preprocessed_path)
+                    } else {
+                        create_dir_all(preprocessed_path.parent().unwrap()).unwrap();
 
-                            eprintln!(
-                                "Generating preprocessed {}.",
-                                preprocessed_path.to_str().unwrap().bright_cyan()
-                            );
-                            let preprocessed = preprocess_election(
-                                &raw_base,
-                                election,
-                                election_path,
-                                &jurisdiction,
-                                contest,
+                        eprintln!(
+                            "Generating preprocessed {}.",
+                            preprocessed_path.to_str().unwrap().bright_cyan()
+                        );
+                        let (preprocessed, trail) = preprocess_election(
+                            &raw_base,
+                            election,
+                            election_path,
+                            &jurisdiction,
+                            contest,
+                            normalization_trail,
+                        );
+                        write_serialized(&preprocessed_path, &preprocessed);
+                        if let Some(trail) = trail {
+                            write_serialized(
+                                &preprocessed_path.with_file_name("normalization_trail.json.gz"),
+                                &trail,
                             );
-                            write_serialized(&preprocessed_path, &preprocessed);
-                            eprintln!("Processed {} ballots", preprocessed.ballots.ballots.len());
+                        }
+                        eprintln!(
+                            "Processed {} ballots",
                             preprocessed
-                        };
+                                .ballots
+                                .ballots
+                                .iter()
+                                .map(|b| b.count)
+                                .sum::<u32>()
+                        );
+                        preprocessed
+                    };
 
-                    let contest_report = generate_report(&preprocessed);
+                    let ballots = preprocessed.ballots.grouped_ballots();
+                    let contest_report = generate_report(&preprocessed, &ballots);
 
                     write_serialized(&report_path, &contest_report);
+
+                    // RAIRE assumes candidates are eliminated one at a time,
+                    // or in batches that can't change the winner.
+                    if preprocessed.info.tabulation_options.is_standard_irv() {
+                        let assertions_path = report_path.with_file_name("assertions.json");
+                        match raire_assertions(&ballots, &contest_report.rounds) {
+                            Some(assertions) => write_serialized(&assertions_path, &assertions),
+                            None => eprintln!("{}", "No audit assertions found.".purple()),
+                        }
+                    }
                     contest_report
                 };
 
@@ -94,8 +126,9 @@ pub fn report(
                     office_name: report.info.office_name.clone(),
                     name: report.info.name.clone(),
                     winner: report.winner().name.clone(),
+                    winners: report.elected().iter().map(|c| c.name.clone()).collect(),
                     num_candidates: report.num_candidates,
-                    num_rounds: report.rounds.len() as u32,
+                    num_rounds: report.num_rounds() as u32,
                 })
             }
 
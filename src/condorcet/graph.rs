@@ -0,0 +1,181 @@
+use crate::condorcet::{count, PairwiseCounts};
+use crate::model::election::CandidateId;
+use crate::model::report::{PairwiseEdge, PairwiseGraph, PairwiseTie};
+use std::cmp::Ordering;
+
+/// Build the graph of pairwise contests between candidates, with an edge
+/// from the winner to the loser of each contest that was not tied.
+pub fn pairwise_graph(candidates: &[CandidateId], counts: &PairwiseCounts) -> PairwiseGraph {
+    let mut edges = Vec::new();
+    let mut ties = Vec::new();
+
+    for (i, a) in candidates.iter().enumerate() {
+        for b in &candidates[i + 1..] {
+            let a_votes = count(counts, *a, *b);
+            let b_votes = count(counts, *b, *a);
+
+            let (winner, loser, strength, losing) = match a_votes.cmp(&b_votes) {
+                Ordering::Greater => (*a, *b, a_votes, b_votes),
+                Ordering::Less => (*b, *a, b_votes, a_votes),
+                Ordering::Equal => {
+                    ties.push(PairwiseTie {
+                        candidates: vec![*a, *b],
+                        votes: a_votes,
+                    });
+                    continue;
+                }
+            };
+
+            edges.push(PairwiseEdge {
+                winner,
+                loser,
+                margin: strength - losing,
+                strength,
+            });
+        }
+    }
+
+    edges.sort_by_key(|e| std::cmp::Reverse((e.margin, e.strength)));
+
+    PairwiseGraph { edges, ties }
+}
+
+/// Compute `reaches[i][j]`: whether candidate `j` can be reached from
+/// candidate `i` by a chain of pairs related by `relation`.
+fn transitive_closure(
+    candidates: &[CandidateId],
+    relation: impl Fn(CandidateId, CandidateId) -> bool,
+) -> Vec<Vec<bool>> {
+    let n = candidates.len();
+    let mut reaches: Vec<Vec<bool>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| i == j || relation(candidates[i], candidates[j]))
+                .collect()
+        })
+        .collect();
+
+    for k in 0..n {
+        let via_k = reaches[k].clone();
+        for row in reaches.iter_mut() {
+            if row[k] {
+                for (r, v) in row.iter_mut().zip(&via_k) {
+                    *r |= *v;
+                }
+            }
+        }
+    }
+
+    reaches
+}
+
+/// Return the Smith set: the smallest set of candidates who each beat every
+/// candidate outside the set. A candidate is in it if they can reach every
+/// other candidate through a chain of pairwise wins or ties.
+pub fn smith_set(candidates: &[CandidateId], counts: &PairwiseCounts) -> Vec<CandidateId> {
+    let reaches = transitive_closure(candidates, |a, b| {
+        count(counts, a, b) >= count(counts, b, a)
+    });
+
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| reaches[*i].iter().all(|r| *r))
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+/// Return the Schwartz set: the union of the smallest sets of candidates who
+/// are unbeaten by any candidate outside the set. A candidate is in it if every
+/// candidate that reaches them through a chain of pairwise wins can also be
+/// reached from them.
+pub fn schwartz_set(candidates: &[CandidateId], counts: &PairwiseCounts) -> Vec<CandidateId> {
+    let reaches = transitive_closure(candidates, |a, b| count(counts, a, b) > count(counts, b, a));
+    let n = candidates.len();
+
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| (0..n).all(|j| !reaches[j][*i] || reaches[*i][j]))
+        .map(|(_, c)| *c)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: CandidateId = CandidateId(0);
+    const B: CandidateId = CandidateId(1);
+    const C: CandidateId = CandidateId(2);
+    const D: CandidateId = CandidateId(3);
+
+    /// Pairwise counts from a list of (a, b, ballots preferring a to b,
+    /// ballots preferring b to a).
+    fn counts(contests: &[(CandidateId, CandidateId, u32, u32)]) -> PairwiseCounts {
+        let mut counts = PairwiseCounts::new();
+        for (a, b, ab, ba) in contests {
+            counts.insert((*a, *b), *ab);
+            counts.insert((*b, *a), *ba);
+        }
+        counts
+    }
+
+    #[test]
+    fn test_condorcet_winner() {
+        let counts = counts(&[(A, B, 60, 40), (A, C, 55, 45), (B, C, 70, 30)]);
+
+        assert_eq!(vec![A], smith_set(&[A, B, C], &counts));
+        assert_eq!(vec![A], schwartz_set(&[A, B, C], &counts));
+    }
+
+    #[test]
+    fn test_cycle() {
+        // A, B and C are in a cycle, and all beat D.
+        let counts = counts(&[
+            (A, B, 60, 40),
+            (B, C, 60, 40),
+            (C, A, 60, 40),
+            (A, D, 70, 30),
+            (B, D, 70, 30),
+            (C, D, 70, 30),
+        ]);
+        let candidates = [A, B, C, D];
+
+        assert_eq!(vec![A, B, C], smith_set(&candidates, &counts));
+        assert_eq!(vec![A, B, C], schwartz_set(&candidates, &counts));
+
+        let graph = pairwise_graph(&candidates, &counts);
+        assert_eq!(6, graph.edges.len());
+        assert!(graph.ties.is_empty());
+        assert_eq!(40, graph.edges[0].margin);
+        assert_eq!(70, graph.edges[0].strength);
+    }
+
+    #[test]
+    fn test_tie() {
+        // A and B are tied, and both beat C.
+        let counts = counts(&[(A, B, 50, 50), (A, C, 60, 40), (B, C, 55, 45)]);
+        let candidates = [A, B, C];
+
+        assert_eq!(vec![A, B], smith_set(&candidates, &counts));
+        assert_eq!(vec![A, B], schwartz_set(&candidates, &counts));
+
+        let graph = pairwise_graph(&candidates, &counts);
+        assert_eq!(vec![A, B], graph.ties[0].candidates);
+        assert_eq!(50, graph.ties[0].votes);
+        assert_eq!(2, graph.edges.len());
+    }
+
+    #[test]
+    fn test_tie_with_beaten_candidate() {
+        // A beats B, B beats C, and A ties C. There is no Condorcet winner,
+        // since A does not beat C. No smaller set beats everyone outside it,
+        // so the Smith set includes everyone, but A alone is unbeaten.
+        let counts = counts(&[(A, B, 60, 40), (B, C, 60, 40), (A, C, 50, 50)]);
+        let candidates = [A, B, C];
+
+        assert_eq!(vec![A, B, C], smith_set(&candidates, &counts));
+        assert_eq!(vec![A], schwartz_set(&candidates, &counts));
+    }
+}
@@ -0,0 +1,16 @@
+use crate::condorcet::{margin, rank_by_key, PairwiseCounts};
+use crate::model::election::CandidateId;
+use crate::model::report::MethodResult;
+
+/// Rank candidates by the Minimax method: each candidate is placed by the
+/// margin of their largest pairwise defeat, smallest first.
+pub fn minimax(candidates: &[CandidateId], counts: &PairwiseCounts) -> MethodResult {
+    rank_by_key(candidates, |c| {
+        candidates
+            .iter()
+            .map(|d| margin(counts, *d, c))
+            .max()
+            .unwrap_or(0)
+            .max(0)
+    })
+}
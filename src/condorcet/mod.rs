@@ -0,0 +1,181 @@
+mod graph;
+mod minimax;
+mod ranked_pairs;
+mod schulze;
+
+use crate::model::election::{CandidateId, NormalizedBallot};
+use crate::model::report::MethodResult;
+use std::collections::{HashMap, HashSet};
+
+pub use graph::{pairwise_graph, schwartz_set, smith_set};
+pub use minimax::minimax;
+pub use ranked_pairs::ranked_pairs;
+pub use schulze::schulze;
+
+/// Map from (a, b) to the number of ballots that rank `a` above `b`.
+pub type PairwiseCounts = HashMap<(CandidateId, CandidateId), u32>;
+
+/// Count, for each ordered pair of candidates (a, b), the ballots that rank
+/// `a` above `b`. A ranked candidate is above every unranked one, and
+/// candidates overvoted at the same ranking are tied with each other.
+pub fn pairwise_counts(candidates: &[CandidateId], ballots: &[NormalizedBallot]) -> PairwiseCounts {
+    let mut preference_map = PairwiseCounts::new();
+    let all_candidates: HashSet<CandidateId> = candidates.iter().copied().collect();
+
+    for ballot in ballots {
+        let mut above_ranked: HashSet<CandidateId> = HashSet::new();
+
+        for ranking in ballot.rankings() {
+            let votes: Vec<CandidateId> = ranking
+                .candidates()
+                .iter()
+                .copied()
+                .filter(|c| !above_ranked.contains(c))
+                .collect();
+
+            for vote in &votes {
+                for arc in &above_ranked {
+                    *preference_map.entry((*arc, *vote)).or_insert(0) += ballot.count;
+                }
+            }
+
+            above_ranked.extend(votes);
+        }
+
+        let remaining = all_candidates.difference(&above_ranked);
+
+        for candidate in remaining {
+            for arc in &above_ranked {
+                *preference_map.entry((*arc, *candidate)).or_insert(0) += ballot.count;
+            }
+        }
+    }
+
+    preference_map
+}
+
+/// Number of ballots that rank `a` above `b`.
+fn count(counts: &PairwiseCounts, a: CandidateId, b: CandidateId) -> u32 {
+    *counts.get(&(a, b)).unwrap_or(&0)
+}
+
+/// Number of ballots that rank `a` above `b`, less the number that rank `b`
+/// above `a`.
+fn margin(counts: &PairwiseCounts, a: CandidateId, b: CandidateId) -> i64 {
+    count(counts, a, b) as i64 - count(counts, b, a) as i64
+}
+
+/// Build a `MethodResult` by grouping candidates into tiers of equal `key`,
+/// where a lower key is a better placement.
+pub fn rank_by_key<K: Ord>(
+    candidates: &[CandidateId],
+    key: impl Fn(CandidateId) -> K,
+) -> MethodResult {
+    let mut keyed: Vec<(K, CandidateId)> = candidates.iter().map(|c| (key(*c), *c)).collect();
+    keyed.sort();
+
+    let mut ranking: Vec<Vec<CandidateId>> = Vec::new();
+    let mut last_key: Option<K> = None;
+    for (k, c) in keyed {
+        if last_key.as_ref() == Some(&k) {
+            ranking.last_mut().unwrap().push(c);
+        } else {
+            ranking.push(vec![c]);
+            last_key = Some(k);
+        }
+    }
+
+    MethodResult::from_ranking(ranking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: CandidateId = CandidateId(0);
+    const B: CandidateId = CandidateId(1);
+    const C: CandidateId = CandidateId(2);
+    const D: CandidateId = CandidateId(3);
+
+    /// Pairwise counts from a list of (number of ballots, full ranking).
+    fn counts(ballots: &[(u32, &[CandidateId])]) -> PairwiseCounts {
+        let mut counts = PairwiseCounts::new();
+        for (n, ranking) in ballots {
+            for (i, a) in ranking.iter().enumerate() {
+                for b in &ranking[i + 1..] {
+                    *counts.entry((*a, *b)).or_default() += n;
+                }
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_condorcet_winner() {
+        // Tennessee capital example: Nashville (B) beats every other city.
+        let counts = counts(&[
+            (42, &[A, B, C, D]),
+            (26, &[B, C, D, A]),
+            (15, &[C, D, B, A]),
+            (17, &[D, C, B, A]),
+        ]);
+        let candidates = [A, B, C, D];
+
+        let expected = vec![vec![B], vec![C], vec![D], vec![A]];
+        assert_eq!(expected, ranked_pairs(&candidates, &counts).ranking);
+        assert_eq!(expected, schulze(&candidates, &counts).ranking);
+
+        // Memphis (A) loses every pairwise contest, but only by 16 votes.
+        assert_eq!(
+            vec![vec![B], vec![A], vec![C], vec![D]],
+            minimax(&candidates, &counts).ranking
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        // A > B by 50, B > C by 30, C > A by 20.
+        let counts = counts(&[(40, &[A, B, C]), (25, &[B, C, A]), (35, &[C, A, B])]);
+        let candidates = [A, B, C];
+
+        // The weakest defeat in the cycle (C > A) is dropped.
+        assert_eq!(
+            vec![vec![A], vec![B], vec![C]],
+            ranked_pairs(&candidates, &counts).ranking
+        );
+        assert_eq!(Some(A), schulze(&candidates, &counts).winner);
+        assert_eq!(
+            vec![vec![A], vec![C], vec![B]],
+            minimax(&candidates, &counts).ranking
+        );
+    }
+
+    #[test]
+    fn test_tie() {
+        let counts = counts(&[(10, &[A, B]), (10, &[B, A])]);
+        let result = schulze(&[A, B], &counts);
+
+        assert_eq!(None, result.winner);
+        assert_eq!(vec![vec![A, B]], result.ranking);
+        assert_eq!(None, minimax(&[A, B], &counts).winner);
+        assert_eq!(None, ranked_pairs(&[A, B], &counts).winner);
+    }
+
+    #[test]
+    fn test_overvote_tied() {
+        use crate::model::election::{BallotEnd, Ranking};
+
+        let ballot = NormalizedBallot::from_rankings(
+            "1".to_string(),
+            vec![Ranking::Overvote(vec![A, B]), Ranking::Candidate(C)],
+            BallotEnd::NoMoreRankings,
+        );
+        let counts = pairwise_counts(&[A, B, C, D], &[ballot]);
+
+        assert_eq!(1, count(&counts, A, C));
+        assert_eq!(1, count(&counts, B, C));
+        assert_eq!(1, count(&counts, C, D));
+        assert_eq!(0, count(&counts, A, B));
+        assert_eq!(0, count(&counts, B, A));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::condorcet::{count, margin, PairwiseCounts};
+use crate::model::election::CandidateId;
+use crate::model::report::MethodResult;
+use std::collections::{BTreeSet, HashMap};
+
+/// Return true if `to` can be reached from `from` by following locked pairs.
+fn reachable(
+    locked: &HashMap<CandidateId, Vec<CandidateId>>,
+    from: CandidateId,
+    to: CandidateId,
+) -> bool {
+    let mut stack = vec![from];
+    let mut seen = BTreeSet::new();
+
+    while let Some(c) = stack.pop() {
+        if c == to {
+            return true;
+        }
+        if seen.insert(c) {
+            stack.extend(locked.get(&c).into_iter().flatten());
+        }
+    }
+
+    false
+}
+
+/// Rank candidates by Tideman's ranked pairs method. Pairwise majorities are
+/// locked in from largest to smallest margin, skipping any that would create
+/// a cycle with those already locked in.
+pub fn ranked_pairs(candidates: &[CandidateId], counts: &PairwiseCounts) -> MethodResult {
+    let mut majorities: Vec<(CandidateId, CandidateId)> = Vec::new();
+    for a in candidates {
+        for b in candidates {
+            if margin(counts, *a, *b) > 0 {
+                majorities.push((*a, *b));
+            }
+        }
+    }
+
+    // Sort by margin, then by the number of winning votes, both descending.
+    majorities.sort_by_key(|(a, b)| {
+        (
+            std::cmp::Reverse(margin(counts, *a, *b)),
+            std::cmp::Reverse(count(counts, *a, *b)),
+        )
+    });
+
+    let mut locked: HashMap<CandidateId, Vec<CandidateId>> = HashMap::new();
+    for (a, b) in majorities {
+        if !reachable(&locked, b, a) {
+            locked.entry(a).or_default().push(b);
+        }
+    }
+
+    // Peel off candidates that no remaining candidate is locked in over.
+    let mut remaining: BTreeSet<CandidateId> = candidates.iter().copied().collect();
+    let mut ranking: Vec<Vec<CandidateId>> = Vec::new();
+
+    while !remaining.is_empty() {
+        let tier: Vec<CandidateId> = remaining
+            .iter()
+            .filter(|c| {
+                !remaining
+                    .iter()
+                    .any(|d| locked.get(d).map(|l| l.contains(c)).unwrap_or(false))
+            })
+            .copied()
+            .collect();
+
+        for c in &tier {
+            remaining.remove(c);
+        }
+        ranking.push(tier);
+    }
+
+    MethodResult::from_ranking(ranking)
+}
@@ -0,0 +1,40 @@
+use crate::condorcet::{count, rank_by_key, PairwiseCounts};
+use crate::model::election::CandidateId;
+use crate::model::report::MethodResult;
+
+/// Rank candidates by the Schulze beatpath method, measuring the strength of
+/// a pairwise defeat by its number of winning votes.
+pub fn schulze(candidates: &[CandidateId], counts: &PairwiseCounts) -> MethodResult {
+    let n = candidates.len();
+
+    // strength[i][j] is the strength of the strongest path from i to j.
+    let mut strength = vec![vec![0u32; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            let d_ij = count(counts, candidates[i], candidates[j]);
+            let d_ji = count(counts, candidates[j], candidates[i]);
+            if i != j && d_ij > d_ji {
+                strength[i][j] = d_ij;
+            }
+        }
+    }
+
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if i != j && i != k && j != k {
+                    let via_k = strength[i][k].min(strength[k][j]);
+                    if via_k > strength[i][j] {
+                        strength[i][j] = via_k;
+                    }
+                }
+            }
+        }
+    }
+
+    // Place each candidate by the number of candidates that beat them.
+    rank_by_key(candidates, |c| {
+        let i = candidates.iter().position(|d| *d == c).unwrap();
+        (0..n).filter(|j| strength[*j][i] > strength[i][*j]).count()
+    })
+}
@@ -10,7 +10,7 @@ pub struct CandidateMap<ExternalCandidateId: Eq + Hash + Clone> {
     candidates: Vec<Candidate>,
 }
 
-impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandidateId> {
+impl<ExternalCandidateId: Eq + Hash + Clone + Debug + ToString> CandidateMap<ExternalCandidateId> {
     pub fn new() -> CandidateMap<ExternalCandidateId> {
         CandidateMap {
             id_to_index: HashMap::new(),
@@ -18,7 +18,8 @@ impl<ExternalCandidateId: Eq + Hash + Clone + Debug> CandidateMap<ExternalCandid
         }
     }
 
-    pub fn add(&mut self, external_candidate_id: ExternalCandidateId, candidate: Candidate) {
+    pub fn add(&mut self, external_candidate_id: ExternalCandidateId, mut candidate: Candidate) {
+        candidate.external_id = Some(external_candidate_id.to_string());
         self.id_to_index.insert(
             external_candidate_id,
             CandidateId(self.candidates.len() as u32),
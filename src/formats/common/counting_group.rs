@@ -0,0 +1,32 @@
+use crate::model::election::CountingGroup;
+
+/// Classify a counting group by the name the ballot data gives it.
+pub fn counting_group(name: &str) -> CountingGroup {
+    let lower = name.to_lowercase();
+    if lower.contains("provisional") {
+        CountingGroup::Provisional
+    } else if lower.contains("mail") || lower.contains("absentee") {
+        CountingGroup::VoteByMail
+    } else if lower.contains("election day") || lower.contains("polling") {
+        CountingGroup::ElectionDay
+    } else {
+        CountingGroup::Other(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counting_group() {
+        assert_eq!(CountingGroup::ElectionDay, counting_group("Election Day"));
+        assert_eq!(CountingGroup::VoteByMail, counting_group("Vote by Mail"));
+        assert_eq!(CountingGroup::VoteByMail, counting_group("ABSENTEE"));
+        assert_eq!(CountingGroup::Provisional, counting_group("Provisional"));
+        assert_eq!(
+            CountingGroup::Other("Early Voting".into()),
+            counting_group("Early Voting")
+        );
+    }
+}
@@ -1,5 +1,9 @@
 mod candidate_map;
+mod counting_group;
 mod normalize_name;
+mod write_ins;
 
 pub use candidate_map::CandidateMap;
+pub use counting_group::counting_group;
 pub use normalize_name::normalize_name;
+pub use write_ins::apply_write_in_policy;
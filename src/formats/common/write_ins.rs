@@ -0,0 +1,162 @@
+use crate::model::election::{Ballot, Candidate, CandidateId, CandidateType, Choice, Election};
+use crate::model::metadata::WriteInPolicy;
+
+/// Name of the candidate that write-ins are merged into.
+const MERGED_WRITE_IN: &str = "Write-in";
+
+/// Return true if `name` is a generic write-in label, like "Write-in" or
+/// "WRITE IN", rather than a candidate's name.
+fn is_write_in_label(name: &str) -> bool {
+    let label: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    label == "writein" || label == "writeins"
+}
+
+/// Apply a write-in policy to an election as read from its raw ballot data.
+/// Candidates that are only a write-in label are treated as unqualified
+/// write-ins, whichever format they came from.
+pub fn apply_write_in_policy(mut election: Election, policy: WriteInPolicy) -> Election {
+    for candidate in &mut election.candidates {
+        if candidate.candidate_type == CandidateType::Regular && is_write_in_label(&candidate.name)
+        {
+            candidate.candidate_type = CandidateType::WriteIn;
+        }
+    }
+
+    if policy == WriteInPolicy::Keep {
+        return election;
+    }
+
+    // Map each old candidate id to its new one, or to `None` if dropped.
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut merged: Option<CandidateId> = None;
+    let mapping: Vec<Option<CandidateId>> = election
+        .candidates
+        .into_iter()
+        .map(|candidate| {
+            if candidate.candidate_type != CandidateType::WriteIn {
+                candidates.push(candidate);
+                return Some(CandidateId(candidates.len() as u32 - 1));
+            }
+
+            match policy {
+                WriteInPolicy::Merge => Some(*merged.get_or_insert_with(|| {
+                    candidates.push(Candidate::new(
+                        MERGED_WRITE_IN.to_string(),
+                        CandidateType::WriteIn,
+                    ));
+                    CandidateId(candidates.len() as u32 - 1)
+                })),
+                _ => None,
+            }
+        })
+        .collect();
+
+    let ballots = election
+        .ballots
+        .into_iter()
+        .map(|ballot| {
+            let Ballot {
+                id,
+                choices,
+                count,
+                overvotes,
+                source,
+            } = ballot;
+            let overvotes = overvotes
+                .into_iter()
+                .map(|(rank, candidates)| {
+                    let mut candidates: Vec<CandidateId> = candidates
+                        .into_iter()
+                        .flat_map(|c| mapping[c.0 as usize])
+                        .collect();
+                    candidates.sort_unstable();
+                    candidates.dedup();
+                    (rank, candidates)
+                })
+                .collect();
+            let choices = choices
+                .into_iter()
+                .map(|choice| match choice {
+                    Choice::Vote(c) => match mapping[c.0 as usize] {
+                        Some(c) => Choice::Vote(c),
+                        None => Choice::Undervote,
+                    },
+                    choice => choice,
+                })
+                .collect();
+            Ballot::with_count(id, choices, count)
+                .with_overvotes(overvotes)
+                .with_source(source)
+        })
+        .collect();
+
+    Election {
+        seats: election.seats,
+        ..Election::new(candidates, ballots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn election() -> Election {
+        let candidates = vec![
+            Candidate::new("Alice".into(), CandidateType::Regular),
+            Candidate::new("Bob".into(), CandidateType::WriteIn),
+            Candidate::new("WRITE-IN".into(), CandidateType::Regular),
+            Candidate::new("Carol".into(), CandidateType::QualifiedWriteIn),
+        ];
+        let votes = |ids: &[u32]| ids.iter().map(|i| Choice::Vote(CandidateId(*i))).collect();
+        Election::new(
+            candidates,
+            vec![
+                Ballot::new("1".into(), votes(&[1, 0])),
+                Ballot::new("2".into(), votes(&[2, 3, 1])),
+            ],
+        )
+    }
+
+    fn choices(election: &Election, ballot: usize) -> Vec<Choice> {
+        election.ballots[ballot].choices.clone()
+    }
+
+    #[test]
+    fn test_keep() {
+        let election = apply_write_in_policy(election(), WriteInPolicy::Keep);
+
+        assert_eq!(4, election.candidates.len());
+        assert_eq!(
+            CandidateType::WriteIn,
+            election.candidates[2].candidate_type
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let election = apply_write_in_policy(election(), WriteInPolicy::Merge);
+        let names: Vec<&str> = election.candidates.iter().map(|c| &c.name[..]).collect();
+        let vote = |i| Choice::Vote(CandidateId(i));
+
+        assert_eq!(vec!["Alice", "Write-in", "Carol"], names);
+        assert_eq!(vec![vote(1), vote(0)], choices(&election, 0));
+        assert_eq!(vec![vote(1), vote(2), vote(1)], choices(&election, 1));
+    }
+
+    #[test]
+    fn test_drop() {
+        let election = apply_write_in_policy(election(), WriteInPolicy::Drop);
+        let vote = |i| Choice::Vote(CandidateId(i));
+
+        assert_eq!(2, election.candidates.len());
+        assert_eq!(vec![Choice::Undervote, vote(0)], choices(&election, 0));
+        assert_eq!(
+            vec![Choice::Undervote, vote(1), Choice::Undervote],
+            choices(&election, 1)
+        );
+    }
+}
@@ -1,10 +1,13 @@
-use crate::formats::common::normalize_name;
-use crate::model::election::{Ballot, Candidate, CandidateId, CandidateType, Choice, Election};
+use crate::formats::common::{counting_group, normalize_name};
+use crate::model::election::{
+    Ballot, BallotSource, Candidate, CandidateId, CandidateType, Choice, Election,
+};
 use nom::{
     character::complete::char, character::complete::digit1, character::complete::line_ending,
     character::complete::not_line_ending, character::complete::tab, combinator::all_consuming,
     multi::count, multi::separated_list1, sequence::terminated, IResult,
 };
+use std::collections::BTreeMap;
 
 pub fn unsigned_int(i: &str) -> IResult<&str, u32> {
     let (i, digits) = digit1(i)?;
@@ -13,7 +16,6 @@ pub fn unsigned_int(i: &str) -> IResult<&str, u32> {
 }
 
 struct RcrHeader {
-    #[allow(unused)]
     pub num_seats: u32,
     pub num_candidates: u32,
     pub num_precincts: u32,
@@ -43,10 +45,10 @@ fn candidate(i: &str) -> IResult<&str, Candidate> {
     ))
 }
 
-fn numbered(i: &str) -> IResult<&str, ()> {
-    let (i, _number) = terminated(unsigned_int, tab)(i)?;
-    let (i, _name) = terminated(not_line_ending, line_ending)(i)?;
-    Ok((i, ()))
+fn numbered(i: &str) -> IResult<&str, (u32, &str)> {
+    let (i, number) = terminated(unsigned_int, tab)(i)?;
+    let (i, name) = terminated(not_line_ending, line_ending)(i)?;
+    Ok((i, (number, name)))
 }
 
 fn choice(i: &str) -> IResult<&str, Choice> {
@@ -61,24 +63,58 @@ fn choice(i: &str) -> IResult<&str, Choice> {
     Ok((i, choice))
 }
 
-fn ballot_entry(i: &str) -> IResult<&str, Choice> {
+/// Parse one ranking, along with the candidates marked if it is an
+/// overvote.
+fn ballot_entry(i: &str) -> IResult<&str, (Choice, Vec<CandidateId>)> {
     let (i, choices) = separated_list1(char('='), choice)(i)?;
-    let choice = match choices.as_slice() {
-        [choice] => *choice,
-        _ => Choice::Overvote,
+    let entry = match choices.as_slice() {
+        [choice] => (*choice, Vec::new()),
+        _ => {
+            let candidates = choices
+                .iter()
+                .flat_map(|c| match c {
+                    Choice::Vote(c) => Some(*c),
+                    _ => None,
+                })
+                .collect();
+            (Choice::Overvote, candidates)
+        }
     };
 
-    Ok((i, choice))
+    Ok((i, entry))
+}
+
+struct BallotLine {
+    precinct: u32,
+    counting_group: u32,
+    count: u32,
+    choices: Vec<Choice>,
+    overvotes: BTreeMap<usize, Vec<CandidateId>>,
 }
 
-fn ballot(i: &str) -> IResult<&str, (u32, Vec<Choice>)> {
-    let (i, _precinct) = terminated(unsigned_int, tab)(i)?;
-    let (i, _counting_group) = terminated(unsigned_int, tab)(i)?;
+fn ballot(i: &str) -> IResult<&str, BallotLine> {
+    let (i, precinct) = terminated(unsigned_int, tab)(i)?;
+    let (i, counting_group) = terminated(unsigned_int, tab)(i)?;
     let (i, ballot_count) = terminated(unsigned_int, tab)(i)?;
 
-    let (i, choices) = separated_list1(tab, ballot_entry)(i)?;
+    let (i, entries) = separated_list1(tab, ballot_entry)(i)?;
+    let mut choices = Vec::new();
+    let mut overvotes = BTreeMap::new();
+    for (rank, (choice, candidates)) in entries.into_iter().enumerate() {
+        if choice == Choice::Overvote {
+            overvotes.insert(rank, candidates);
+        }
+        choices.push(choice);
+    }
 
-    Ok((i, (ballot_count, choices)))
+    let line = BallotLine {
+        precinct,
+        counting_group,
+        count: ballot_count,
+        choices,
+        overvotes,
+    };
+    Ok((i, line))
 }
 
 pub fn parse_rcr_file(i: &str) -> IResult<&str, Election> {
@@ -89,23 +125,92 @@ pub fn parse_rcr_file(i: &str) -> IResult<&str, Election> {
     let (i, _name) = terminated(not_line_ending, line_ending)(i)?;
 
     let (i, candidates) = count(candidate, header.num_candidates as usize)(i)?;
-    let (i, _) = count(numbered, header.num_precincts as usize)(i)?;
-    let (i, _) = count(numbered, header.num_counting_groups as usize)(i)?;
+    let (i, precincts) = count(numbered, header.num_precincts as usize)(i)?;
+    let (i, counting_groups) = count(numbered, header.num_counting_groups as usize)(i)?;
+    let precincts: BTreeMap<u32, &str> = precincts.into_iter().collect();
+    let counting_groups: BTreeMap<u32, &str> = counting_groups.into_iter().collect();
 
     let (i, agg_ballots) = terminated(separated_list1(line_ending, ballot), line_ending)(i)?;
 
-    let mut ballots: Vec<Ballot> = Vec::new();
+    let ballots: Vec<Ballot> = agg_ballots
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let source = BallotSource {
+                precinct: precincts.get(&line.precinct).map(|p| p.to_string()),
+                counting_group: counting_groups
+                    .get(&line.counting_group)
+                    .map(|g| counting_group(g)),
+                batch: None,
+            };
+            Ballot::with_count(i.to_string(), line.choices, line.count)
+                .with_overvotes(line.overvotes)
+                .with_source(source)
+        })
+        .collect();
 
-    for (num, choices) in agg_ballots {
-        for _ in 0..num {
-            ballots.push(Ballot::new(ballots.len().to_string(), choices.clone()));
-        }
-    }
-
-    Ok((i, Election::new(candidates, ballots)))
+    Ok((
+        i,
+        Election::new(candidates, ballots).with_seats(header.num_seats),
+    ))
 }
 
 pub fn rcr_file(i: &str) -> Election {
     let (_, result) = all_consuming(parse_rcr_file)(i).unwrap();
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CountingGroup;
+
+    const RCR: &str = "2\t3\t1\t1\nCity Council\nAlice\nBob\nCarol\n1\tPct 1\n1\tElection Day\n1\t1\t4\t1\t2\n1\t1\t2\t3\t0\n";
+
+    #[test]
+    fn test_rcr_file() {
+        let election = rcr_file(RCR);
+
+        assert_eq!(Some(2), election.seats);
+        assert_eq!(3, election.candidates.len());
+        assert_eq!(2, election.ballots.len());
+        assert_eq!(4, election.ballots[0].count);
+        assert_eq!(
+            vec![Choice::Vote(CandidateId(2)), Choice::Undervote],
+            election.ballots[1].choices
+        );
+    }
+
+    #[test]
+    fn test_ballot() {
+        let (rest, line) = ballot("2\t1\t3\t1=2\t3").unwrap();
+
+        assert_eq!("", rest);
+        assert_eq!(2, line.precinct);
+        assert_eq!(1, line.counting_group);
+        assert_eq!(3, line.count);
+        assert_eq!(
+            vec![Choice::Overvote, Choice::Vote(CandidateId(2))],
+            line.choices
+        );
+        assert_eq!(
+            Some(&vec![CandidateId(0), CandidateId(1)]),
+            line.overvotes.get(&0)
+        );
+    }
+
+    #[test]
+    fn test_ballot_source() {
+        let rcr = "1\t2\t2\t2\nMayor\nAlice\nBob\n1\tPct 1\n2\tPct 2\n1\tElection Day\n2\tVote by Mail\n2\t2\t1\t1\t2\n";
+        let election = rcr_file(rcr);
+
+        assert_eq!(
+            BallotSource {
+                precinct: Some("Pct 2".into()),
+                counting_group: Some(CountingGroup::VoteByMail),
+                batch: None,
+            },
+            election.ballots[0].source
+        );
+    }
+}
@@ -7,7 +7,9 @@ mod us_me;
 mod us_ny_nyc;
 mod us_vt_btv;
 
+use crate::formats::common::apply_write_in_policy;
 use crate::model::election::Election;
+use crate::model::metadata::WriteInPolicy;
 use std::collections::BTreeMap;
 use std::path::Path;
 
@@ -26,7 +28,24 @@ pub fn get_reader_for_format(format: &str) -> &'static BallotReader {
     }
 }
 
-pub fn read_election(format: &str, path: &Path, params: BTreeMap<String, String>) -> Election {
+pub fn read_election(
+    format: &str,
+    path: &Path,
+    params: BTreeMap<String, String>,
+    write_ins: Option<WriteInPolicy>,
+) -> Election {
+    // Before write-in policies, `nist_sp_1500` had its own parameter for
+    // dropping write-ins.
+    let legacy_drop = params
+        .get("dropUnqualifiedWriteIn")
+        .map(|d| d.parse().unwrap())
+        .unwrap_or(false);
+    let write_ins = match (write_ins, legacy_drop) {
+        (Some(policy), _) => policy,
+        (None, true) => WriteInPolicy::Drop,
+        (None, false) => WriteInPolicy::Keep,
+    };
+
     let reader = get_reader_for_format(format);
-    reader(path, params)
+    apply_write_in_policy(reader(path, params), write_ins)
 }
@@ -1,8 +1,11 @@
 pub mod model;
 
-use crate::formats::common::{normalize_name, CandidateMap};
-use crate::formats::nist_sp_1500::model::{CandidateManifest, CandidateType, CvrExport, Mark};
-use crate::model::election::{self, Ballot, Candidate, Choice, Election};
+use crate::formats::common::{counting_group, normalize_name, CandidateMap};
+use crate::formats::nist_sp_1500::model::{
+    CandidateManifest, CandidateType, CountingGroupManifest, CvrExport, Mark,
+    PrecinctPortionManifest,
+};
+use crate::model::election::{self, Ballot, BallotSource, Candidate, Choice, Election};
 use colored::*;
 use itertools::Itertools;
 use std::collections::BTreeMap;
@@ -14,7 +17,6 @@ use std::path::Path;
 struct ReaderOptions {
     cvr: String,
     contest: u32,
-    drop_unqualified_write_in: bool,
 }
 
 impl ReaderOptions {
@@ -28,26 +30,12 @@ impl ReaderOptions {
             .expect("nist_sp_1500 elections should have contest parameter.")
             .parse()
             .expect("contest param should be a number.");
-        let drop_unqualified_write_in: bool = params
-            .get("dropUnqualifiedWriteIn")
-            .map(|d| d.parse().unwrap())
-            .unwrap_or(false);
-
-        ReaderOptions {
-            contest,
-            cvr,
-            drop_unqualified_write_in,
-        }
+        ReaderOptions { contest, cvr }
     }
 }
 
-fn get_candidates(
-    manifest: &CandidateManifest,
-    contest_id: u32,
-    drop_unqualified_write_in: bool,
-) -> (CandidateMap<u32>, Option<u32>) {
+fn get_candidates(manifest: &CandidateManifest, contest_id: u32) -> CandidateMap<u32> {
     let mut map = CandidateMap::new();
-    let mut write_in_external_id = None;
 
     for candidate in &manifest.list {
         if candidate.contest_id == contest_id {
@@ -57,11 +45,6 @@ fn get_candidates(
                 CandidateType::Regular => election::CandidateType::Regular,
             };
 
-            if drop_unqualified_write_in && candidate_type == election::CandidateType::WriteIn {
-                write_in_external_id = Some(candidate.id);
-                continue;
-            }
-
             map.add(
                 candidate.id,
                 Candidate::new(
@@ -72,15 +55,16 @@ fn get_candidates(
         }
     }
 
-    (map, write_in_external_id)
+    map
 }
 
 fn get_ballots(
     cvr: &CvrExport,
     contest_id: u32,
     map: &CandidateMap<u32>,
+    precincts: &BTreeMap<u32, String>,
+    counting_groups: &BTreeMap<u32, election::CountingGroup>,
     filename: &str,
-    dropped_write_in: Option<u32>,
 ) -> Vec<Ballot> {
     let mut ballots: Vec<Ballot> = Vec::new();
 
@@ -88,29 +72,49 @@ fn get_ballots(
         for contest in &session.contests() {
             if contest.id == contest_id {
                 let mut choices: Vec<Choice> = Vec::new();
+                let mut overvotes = BTreeMap::new();
                 for (_, marks) in &contest.marks.iter().group_by(|x| x.rank) {
                     let marks: Vec<&Mark> = marks.filter(|d| !d.is_ambiguous).collect();
 
                     let choice = match marks.as_slice() {
-                        [v] if Some(v.candidate_id) == dropped_write_in => {
-                            // The standard way of handling write-ins with CVR files seems to
-                            // be that write-in candidates who reach a certain threshold are
-                            // promoted to "QualifiedWriteIn" type. For tabulation, unqualified
-                            // write-in candidates are dropped by treating them as undervotes.
-                            Choice::Undervote
-                        }
                         [v] => map.id_to_choice(v.candidate_id),
                         [] => Choice::Undervote,
-                        _ => Choice::Overvote,
+                        _ => {
+                            let candidates = marks
+                                .iter()
+                                .flat_map(|m| match map.id_to_choice(m.candidate_id) {
+                                    Choice::Vote(c) => Some(c),
+                                    _ => None,
+                                })
+                                .collect();
+                            overvotes.insert(choices.len(), candidates);
+                            Choice::Overvote
+                        }
                     };
 
                     choices.push(choice);
                 }
 
-                ballots.push(Ballot::new(
-                    format!("{}:{}", filename, session.record_id),
-                    choices,
-                ));
+                // Precincts are named by the precinct portion the ballot
+                // belongs to, or by its id if the export has no precinct
+                // portion manifest. Batch numbers are only unique within a
+                // tabulator.
+                let precinct_portion_id = session.ballot().precinct_portion_id;
+                let source = BallotSource {
+                    precinct: Some(
+                        precincts
+                            .get(&precinct_portion_id)
+                            .cloned()
+                            .unwrap_or_else(|| precinct_portion_id.to_string()),
+                    ),
+                    counting_group: counting_groups.get(&session.counting_group_id).cloned(),
+                    batch: Some(format!("{}:{}", session.tabulator_id, session.batch_id)),
+                };
+                ballots.push(
+                    Ballot::new(format!("{}:{}", filename, session.record_id), choices)
+                        .with_overvotes(overvotes)
+                        .with_source(source),
+                );
             }
         }
     }
@@ -130,11 +134,36 @@ pub fn nist_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elec
         serde_json::from_reader(reader).unwrap()
     };
 
-    let (candidates, dropped_write_in) = get_candidates(
-        &candidate_manifest,
-        options.contest,
-        options.drop_unqualified_write_in,
-    );
+    let candidates = get_candidates(&candidate_manifest, options.contest);
+
+    // Not every export includes the precinct portion and counting group
+    // manifests.
+    let precincts: BTreeMap<u32, String> = match archive.by_name("PrecinctPortionManifest.json") {
+        Ok(file) => {
+            let manifest: PrecinctPortionManifest =
+                serde_json::from_reader(BufReader::new(file)).unwrap();
+            manifest
+                .list
+                .into_iter()
+                .map(|p| (p.id, p.description))
+                .collect()
+        }
+        Err(_) => BTreeMap::new(),
+    };
+
+    let counting_groups: BTreeMap<u32, election::CountingGroup> =
+        match archive.by_name("CountingGroupManifest.json") {
+            Ok(file) => {
+                let manifest: CountingGroupManifest =
+                    serde_json::from_reader(BufReader::new(file)).unwrap();
+                manifest
+                    .list
+                    .iter()
+                    .map(|g| (g.id, counting_group(&g.description)))
+                    .collect()
+            }
+            Err(_) => BTreeMap::new(),
+        };
 
     let mut ballots: Vec<Ballot> = Default::default();
     let filenames: Vec<String> = archive.file_names().map(|d| d.to_string()).collect();
@@ -149,8 +178,9 @@ pub fn nist_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elec
                 &cvr,
                 options.contest,
                 &candidates,
+                &precincts,
+                &counting_groups,
                 &filename,
-                dropped_write_in,
             );
             ballots.extend(extra_ballots);
         }
@@ -13,10 +13,10 @@ pub struct CvrExport {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Session {
-    tabulator_id: u32,
-    batch_id: u32,
+    pub tabulator_id: u32,
+    pub batch_id: u32,
     pub record_id: u32,
-    counting_group_id: u32,
+    pub counting_group_id: u32,
     image_mask: String,
     original: SessionBallot,
     modified: Option<SessionBallot>,
@@ -49,7 +49,7 @@ impl Session {
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct SessionBallot {
-    precinct_portion_id: u32,
+    pub precinct_portion_id: u32,
     ballot_type_id: u32,
     is_current: bool,
     contests: Option<Vec<ContestMarks>>,
@@ -110,8 +110,41 @@ pub struct Candidate {
     pub candidate_type: CandidateType,
 }
 
+// CountingGroupManifest.json
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CountingGroupManifest {
+    version: String,
+    pub list: Vec<CountingGroup>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CountingGroup {
+    pub description: String,
+    pub id: u32,
+}
+
+// PrecinctPortionManifest.json
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PrecinctPortionManifest {
+    version: String,
+    pub list: Vec<PrecinctPortion>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PrecinctPortion {
+    pub description: String,
+    pub id: u32,
+}
+
 // ContestManifest.json
 
+#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ContestManifest {
@@ -119,6 +152,7 @@ pub struct ContestManifest {
     list: Vec<Contest>,
 }
 
+#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Contest {
@@ -1,5 +1,7 @@
-use crate::formats::common::{normalize_name, CandidateMap};
-use crate::model::election::{Ballot, Candidate, CandidateType, Choice, Election};
+use crate::formats::common::{counting_group, normalize_name, CandidateMap};
+use crate::model::election::{
+    Ballot, BallotSource, Candidate, CandidateType, Choice, CountingGroup, Election,
+};
 use crate::util::UnicodeString;
 use itertools::Itertools;
 use std::collections::BTreeMap;
@@ -8,6 +10,8 @@ use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 const CANDIDATE: &str = "Candidate";
+const PRECINCT: &str = "Precinct";
+const TALLY_TYPE: &str = "Tally Type";
 const WRITE_IN_PREFIX: &str = "WRITE-IN ";
 
 #[derive(Debug)]
@@ -41,8 +45,8 @@ struct BallotRecord {
     contest_id: u32,
     pref_voter_id: u32,
     _serial_number: u32,
-    _tally_type_id: u32,
-    _precinct_id: u32,
+    tally_type_id: u32,
+    precinct_id: u32,
     vote_rank: u32,
     candidate_id: u32,
     over_vote: bool,
@@ -57,8 +61,8 @@ impl BallotRecord {
             contest_id: input.slice(0..7).parse().unwrap(),
             pref_voter_id: input.slice(7..16).parse().unwrap(),
             _serial_number: input.slice(16..23).parse().unwrap(),
-            _tally_type_id: input.slice(23..26).parse().unwrap(),
-            _precinct_id: input.slice(26..33).parse().unwrap(),
+            tally_type_id: input.slice(23..26).parse().unwrap(),
+            precinct_id: input.slice(26..33).parse().unwrap(),
             vote_rank: input.slice(33..36).parse().unwrap(),
             candidate_id: input.slice(36..43).parse().unwrap(),
             over_vote: &input.slice(43..44) == "1",
@@ -67,13 +71,25 @@ impl BallotRecord {
     }
 }
 
-fn read_candidates(reader: &mut dyn BufRead, contest_id: u32) -> CandidateMap<u32> {
+struct MasterLookup {
+    candidates: CandidateMap<u32>,
+    precincts: BTreeMap<u32, String>,
+    tally_types: BTreeMap<u32, CountingGroup>,
+}
+
+fn read_master(reader: &mut dyn BufRead, contest_id: u32) -> MasterLookup {
     let mut candidates = CandidateMap::new();
+    let mut precincts = BTreeMap::new();
+    let mut tally_types = BTreeMap::new();
     for line in reader.lines() {
         let line = line.unwrap();
         let record = MasterRecord::parse(&line);
 
-        if record.record_type == CANDIDATE {
+        if record.record_type == PRECINCT {
+            precincts.insert(record.record_id, record.description);
+        } else if record.record_type == TALLY_TYPE {
+            tally_types.insert(record.record_id, counting_group(&record.description));
+        } else if record.record_type == CANDIDATE {
             if record.contest_id != contest_id {
                 continue;
             }
@@ -94,27 +110,41 @@ fn read_candidates(reader: &mut dyn BufRead, contest_id: u32) -> CandidateMap<u3
             candidates.add(record.record_id, candidate);
         }
     }
-    candidates
+    MasterLookup {
+        candidates,
+        precincts,
+        tally_types,
+    }
 }
 
-fn read_ballots(
-    reader: &mut dyn BufRead,
-    candidates: &CandidateMap<u32>,
-    contest: u32,
-) -> Vec<Ballot> {
+fn read_ballots(reader: &mut dyn BufRead, master: &MasterLookup, contest: u32) -> Vec<Ballot> {
     let mut ballots = Vec::new();
 
     for (id, votes) in reader
         .lines()
-        .into_iter()
         .map(|v| BallotRecord::parse(&v.unwrap()))
         .filter(|v| v.contest_id == contest)
         .group_by(|v| v.pref_voter_id)
         .into_iter()
     {
         let mut choices = Vec::new();
+        let mut source = BallotSource::default();
 
         for (i, ballot_record) in votes.enumerate() {
+            if i == 0 {
+                let precinct = ballot_record.precinct_id;
+                source.precinct = Some(
+                    master
+                        .precincts
+                        .get(&precinct)
+                        .cloned()
+                        .unwrap_or_else(|| precinct.to_string()),
+                );
+                source.counting_group = master
+                    .tally_types
+                    .get(&ballot_record.tally_type_id)
+                    .cloned();
+            }
             if ballot_record.vote_rank != (i + 1) as u32 {
                 panic!("Got record out of order.")
             }
@@ -123,11 +153,11 @@ fn read_ballots(
             } else if ballot_record.under_vote {
                 choices.push(Choice::Undervote)
             } else {
-                choices.push(candidates.id_to_choice(ballot_record.candidate_id))
+                choices.push(master.candidates.id_to_choice(ballot_record.candidate_id))
             }
         }
 
-        ballots.push(Ballot::new(id.to_string(), choices))
+        ballots.push(Ballot::new(id.to_string(), choices).with_source(source))
     }
     ballots
 }
@@ -168,30 +198,30 @@ impl ReaderOptions {
 pub fn sfo_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
     let options = ReaderOptions::from_params(params);
 
-    let (candidates, ballots) = if let Some(zip_file) = options.zip_file {
+    let (master, ballots) = if let Some(zip_file) = options.zip_file {
         let file = File::open(path.join(&zip_file)).unwrap();
         let mut archive = zip::ZipArchive::new(file).unwrap();
-        let candidates = {
+        let master = {
             let master = archive.by_name(&options.master_file).unwrap();
             let mut master_reader = BufReader::new(master);
-            read_candidates(&mut master_reader, options.contest)
+            read_master(&mut master_reader, options.contest)
         };
 
         let ballots = {
             let ballots = archive.by_name(&options.ballot_file).unwrap();
             let mut ballot_reader = BufReader::new(ballots);
-            read_ballots(&mut ballot_reader, &candidates, options.contest)
+            read_ballots(&mut ballot_reader, &master, options.contest)
         };
 
-        (candidates, ballots)
+        (master, ballots)
     } else {
         let mut master_reader = BufReader::new(File::open(path.join(options.master_file)).unwrap());
-        let candidates = read_candidates(&mut master_reader, options.contest);
+        let master = read_master(&mut master_reader, options.contest);
 
         let mut ballot_reader = BufReader::new(File::open(path.join(options.ballot_file)).unwrap());
-        let ballots = read_ballots(&mut ballot_reader, &candidates, options.contest);
-        (candidates, ballots)
+        let ballots = read_ballots(&mut ballot_reader, &master, options.contest);
+        (master, ballots)
     };
 
-    Election::new(candidates.into_vec(), ballots)
+    Election::new(master.candidates.into_vec(), ballots)
 }
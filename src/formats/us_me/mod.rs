@@ -48,7 +48,7 @@ pub fn parse_choice(candidate: &str, candidate_map: &mut CandidateMap<String>) -
 }
 
 pub fn read_ballot(row: &[DataType], candidate_map: &mut CandidateMap<String>) -> Ballot {
-    let id = row.get(0).unwrap().get_float().unwrap() as u32;
+    let id = row.first().unwrap().get_float().unwrap() as u32;
 
     let mut choices = Vec::new();
     for vote in &row[3..] {
@@ -41,7 +41,7 @@ pub fn read_candidate_ids(workbook: &mut Sheets) -> HashMap<u32, String> {
     let mut rows = sheet.rows();
     rows.next();
     for row in rows {
-        let id = row.get(0).unwrap().get_float().unwrap() as u32;
+        let id = row.first().unwrap().get_float().unwrap() as u32;
         let name = row.get(1).unwrap().get_string().unwrap();
 
         candidates.insert(id, name.to_string());
@@ -25,27 +25,36 @@ impl ReaderOptions {
     }
 }
 
-pub fn parse_ballot(source: &str) -> Vec<Choice> {
-    if source.is_empty() {
-        return vec![];
+fn parse_candidate(rank: &str) -> CandidateId {
+    match rank.strip_prefix('C') {
+        Some(candidate_id) => {
+            let candidate_id: u32 = candidate_id.parse().unwrap();
+            CandidateId(candidate_id - 1)
+        }
+        None => panic!("Bad candidate list ({}).", rank),
     }
+}
 
-    let ranks = source.split(',');
+/// Parse a ballot's rankings, along with the candidates marked at each
+/// overvoted ranking.
+pub fn parse_ballot(source: &str) -> (Vec<Choice>, BTreeMap<usize, Vec<CandidateId>>) {
     let mut choices = Vec::new();
+    let mut overvotes = BTreeMap::new();
+    if source.is_empty() {
+        return (choices, overvotes);
+    }
 
-    for rank in ranks {
+    for (i, rank) in source.split(',').enumerate() {
         let choice = if rank.contains('=') {
+            overvotes.insert(i, rank.split('=').map(parse_candidate).collect());
             Choice::Overvote
-        } else if let Some(candidate_id) = rank.strip_prefix('C') {
-            let candidate_id: u32 = candidate_id.parse().unwrap();
-            Choice::Vote(CandidateId(candidate_id - 1))
         } else {
-            panic!("Bad candidate list ({}).", rank)
+            Choice::Vote(parse_candidate(rank))
         };
         choices.push(choice);
     }
 
-    choices
+    (choices, overvotes)
 }
 
 pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Election {
@@ -80,16 +89,13 @@ pub fn btv_ballot_reader(path: &Path, params: BTreeMap<String, String>) -> Elect
             let id: &str = caps.get(1).unwrap().as_str();
             let votes: &str = caps.get(2).unwrap().as_str();
 
-            let choices = parse_ballot(votes);
-            let ballot = Ballot::new(id.into(), choices);
+            let (choices, overvotes) = parse_ballot(votes);
+            let ballot = Ballot::new(id.into(), choices).with_overvotes(overvotes);
             ballots.push(ballot);
         }
     }
 
-    Election {
-        candidates,
-        ballots,
-    }
+    Election::new(candidates, ballots)
 }
 
 #[cfg(test)]
@@ -98,18 +104,23 @@ mod tests {
 
     #[test]
     fn test_parse_ballot() {
-        assert_eq!(Vec::new() as Vec<Choice>, parse_ballot(""));
+        assert_eq!(Vec::new() as Vec<Choice>, parse_ballot("").0);
 
-        assert_eq!(vec![Choice::Vote(CandidateId(3))], parse_ballot("C04"));
+        assert_eq!(vec![Choice::Vote(CandidateId(3))], parse_ballot("C04").0);
 
         assert_eq!(
             vec![Choice::Vote(CandidateId(3)), Choice::Vote(CandidateId(2))],
-            parse_ballot("C04,C03")
+            parse_ballot("C04,C03").0
         );
 
+        let (choices, overvotes) = parse_ballot("C04=C06,C03");
         assert_eq!(
             vec![Choice::Overvote, Choice::Vote(CandidateId(2))],
-            parse_ballot("C04=C06,C03")
+            choices
+        );
+        assert_eq!(
+            Some(&vec![CandidateId(3), CandidateId(5)]),
+            overvotes.get(&0)
         );
     }
 }
@@ -1,5 +1,9 @@
+mod alternative;
+mod audit;
 mod commands;
+mod condorcet;
 mod formats;
+mod margin;
 mod model;
 mod normalizers;
 mod read_metadata;
@@ -7,7 +11,7 @@ mod report;
 mod tabulator;
 mod util;
 
-use crate::commands::{info, report, sync};
+use crate::commands::{audit, info, report, sync};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -44,6 +48,31 @@ enum Command {
         /// Whether to force preprocessing even if preprocessed files exist
         force_preprocess: bool,
         force_report: bool,
+        /// Write which normalization rules applied to each ballot next to
+        /// the preprocessed file. Implies forcing preprocessing
+        #[clap(long)]
+        normalization_trail: bool,
+    },
+    /// Simulate a risk-limiting audit of a preprocessed IRV contest
+    Audit {
+        /// Preprocessed election file
+        preprocessed_file: PathBuf,
+        /// Kind of audit, `ballotPolling` or `comparison`
+        #[clap(long, default_value = "ballotPolling")]
+        kind: String,
+        /// Largest chance of confirming a wrong outcome
+        #[clap(long, default_value = "0.05")]
+        risk_limit: f64,
+        /// Share of sampled ballots whose record overstates the margins by
+        /// one vote, for comparison audits
+        #[clap(long, default_value = "0")]
+        error_rate: f64,
+        /// Number of audits to simulate
+        #[clap(long, default_value = "100")]
+        trials: u32,
+        /// Seed for drawing ballots
+        #[clap(long, default_value = "0")]
+        seed: u64,
     },
 }
 
@@ -67,6 +96,7 @@ fn main() {
             report_dir,
             force_preprocess,
             force_report,
+            normalization_trail,
         } => {
             report(
                 &meta_dir,
@@ -75,6 +105,24 @@ fn main() {
                 &preprocessed_dir,
                 force_preprocess,
                 force_report,
+                normalization_trail,
+            );
+        }
+        Command::Audit {
+            preprocessed_file,
+            kind,
+            risk_limit,
+            error_rate,
+            trials,
+            seed,
+        } => {
+            audit(
+                &preprocessed_file,
+                &kind,
+                risk_limit,
+                error_rate,
+                trials,
+                seed,
             );
         }
     }
@@ -0,0 +1,252 @@
+//! A small integer linear program solver: a dual simplex method on a dense
+//! tableau, with branch and bound for integrality. It is meant for the
+//! programs of a few dozen constraints that margin calculations produce.
+
+/// Tolerance for treating a value as zero or as an integer.
+const EPSILON: f64 = 1e-6;
+
+/// A constraint `coefficients · x ≤ bound`.
+pub struct Constraint {
+    pub coefficients: Vec<f64>,
+    pub bound: f64,
+}
+
+#[derive(Clone)]
+struct Tableau {
+    /// Coefficients of each row, over the structural variables followed by
+    /// one slack variable per row.
+    rows: Vec<Vec<f64>>,
+    rhs: Vec<f64>,
+    /// Reduced cost of each variable.
+    reduced_costs: Vec<f64>,
+    /// Cost of each structural variable.
+    costs: Vec<f64>,
+    /// The basic variable of each row.
+    basis: Vec<usize>,
+}
+
+impl Tableau {
+    /// Start from the basis of slack variables, which is dual feasible
+    /// because no cost is negative.
+    fn new(costs: &[f64]) -> Tableau {
+        Tableau {
+            rows: Vec::new(),
+            rhs: Vec::new(),
+            reduced_costs: costs.to_vec(),
+            costs: costs.to_vec(),
+            basis: Vec::new(),
+        }
+    }
+
+    /// Add a constraint over the structural variables, expressing it in
+    /// terms of the current non-basic variables.
+    fn add_row(&mut self, coefficients: &[f64], bound: f64) {
+        let columns = self.reduced_costs.len();
+        let mut row = coefficients.to_vec();
+        row.resize(columns, 0.);
+        let mut rhs = bound;
+
+        for (i, basic) in self.basis.iter().enumerate() {
+            let factor = row[*basic];
+            if factor.abs() > 0. {
+                for (value, other) in row.iter_mut().zip(&self.rows[i]) {
+                    *value -= factor * other;
+                }
+                rhs -= factor * self.rhs[i];
+            }
+        }
+
+        for other in &mut self.rows {
+            other.push(0.);
+        }
+        row.push(1.);
+        self.rows.push(row);
+        self.rhs.push(rhs);
+        self.reduced_costs.push(0.);
+        self.basis.push(columns);
+    }
+
+    fn pivot(&mut self, r: usize, j: usize) {
+        let pivot = self.rows[r][j];
+        for value in &mut self.rows[r] {
+            *value /= pivot;
+        }
+        self.rhs[r] /= pivot;
+
+        let pivot_row = self.rows[r].clone();
+        for i in 0..self.rows.len() {
+            let factor = self.rows[i][j];
+            if i != r && factor.abs() > 0. {
+                for (value, other) in self.rows[i].iter_mut().zip(&pivot_row) {
+                    *value -= factor * other;
+                }
+                self.rhs[i] -= factor * self.rhs[r];
+            }
+        }
+
+        let factor = self.reduced_costs[j];
+        for (value, other) in self.reduced_costs.iter_mut().zip(&pivot_row) {
+            *value -= factor * other;
+        }
+        self.basis[r] = j;
+    }
+
+    /// Run the dual simplex method to optimality. Returns false if the
+    /// constraints cannot be met. Bland's rule picks the pivots, so this
+    /// cannot cycle.
+    fn solve(&mut self) -> bool {
+        loop {
+            let leaving = (0..self.rows.len())
+                .filter(|i| self.rhs[*i] < -EPSILON)
+                .min_by_key(|i| self.basis[*i]);
+            let r = match leaving {
+                Some(r) => r,
+                None => return true,
+            };
+
+            let mut entering: Option<(usize, f64)> = None;
+            for (j, value) in self.rows[r].iter().enumerate() {
+                if *value < -EPSILON {
+                    let ratio = self.reduced_costs[j] / -value;
+                    if entering.is_none_or(|(_, best)| ratio < best - EPSILON) {
+                        entering = Some((j, ratio));
+                    }
+                }
+            }
+
+            match entering {
+                Some((j, _)) => self.pivot(r, j),
+                None => return false,
+            }
+        }
+    }
+
+    fn solution(&self) -> Vec<f64> {
+        let mut x = vec![0.; self.costs.len()];
+        for (i, basic) in self.basis.iter().enumerate() {
+            if *basic < x.len() {
+                x[*basic] = self.rhs[i];
+            }
+        }
+        x
+    }
+
+    fn value(&self, x: &[f64]) -> f64 {
+        x.iter().zip(&self.costs).map(|(x, c)| x * c).sum()
+    }
+}
+
+struct Search<'a> {
+    upper: &'a [Option<f64>],
+    /// Only solutions cheaper than this are of interest.
+    cutoff: f64,
+    best: Option<Vec<i64>>,
+}
+
+impl<'a> Search<'a> {
+    fn branch(&mut self, mut tableau: Tableau) {
+        // Upper bounds rarely matter, so they are only added as constraints
+        // once a solution breaks them.
+        let x = loop {
+            if !tableau.solve() {
+                return;
+            }
+            let x = tableau.solution();
+            let broken =
+                (0..x.len()).find(|j| matches!(self.upper[*j], Some(u) if x[*j] > u + EPSILON));
+            match broken {
+                Some(j) => tableau.add_row(&unit(x.len(), j, 1.), self.upper[j].unwrap()),
+                None => break x,
+            }
+        };
+
+        // Costs are whole numbers, so any integer solution costs at least the
+        // LP optimum rounded up.
+        let value = tableau.value(&x);
+        if (value - EPSILON).ceil() >= self.cutoff {
+            return;
+        }
+
+        match (0..x.len()).find(|j| (x[*j] - x[*j].round()).abs() > EPSILON) {
+            Some(j) => {
+                let mut down = tableau.clone();
+                down.add_row(&unit(x.len(), j, 1.), x[j].floor());
+                self.branch(down);
+
+                tableau.add_row(&unit(x.len(), j, -1.), -x[j].ceil());
+                self.branch(tableau);
+            }
+            None => {
+                self.cutoff = value.round();
+                self.best = Some(x.iter().map(|v| v.round() as i64).collect());
+            }
+        }
+    }
+}
+
+fn unit(n: usize, j: usize, value: f64) -> Vec<f64> {
+    let mut v = vec![0.; n];
+    v[j] = value;
+    v
+}
+
+/// Minimize `costs · x` over non-negative integer `x` that meets every
+/// constraint and is no more than the given upper bounds. Costs must be
+/// non-negative whole numbers. Returns the cheapest solution costing less
+/// than `cutoff`, if there is one.
+pub fn minimize(
+    costs: &[f64],
+    constraints: &[Constraint],
+    upper: &[Option<f64>],
+    cutoff: f64,
+) -> Option<Vec<i64>> {
+    let mut tableau = Tableau::new(costs);
+    for constraint in constraints {
+        tableau.add_row(&constraint.coefficients, constraint.bound);
+    }
+
+    let mut search = Search {
+        upper,
+        cutoff,
+        best: None,
+    };
+    search.branch(tableau);
+    search.best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimize() {
+        // x + y >= 1.5, so the cheapest integer solution has x + y = 2.
+        let constraints = vec![Constraint {
+            coefficients: vec![-1., -1.],
+            bound: -1.5,
+        }];
+        let solution = minimize(&[1., 1.], &constraints, &[None, None], f64::INFINITY).unwrap();
+        assert_eq!(2, solution.iter().sum::<i64>());
+
+        let solution = minimize(&[1., 3.], &constraints, &[Some(1.), None], f64::INFINITY);
+        assert_eq!(Some(vec![1, 1]), solution);
+
+        assert_eq!(None, minimize(&[1., 1.], &constraints, &[None, None], 2.));
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // x <= 1 and x >= 2.
+        let constraints = vec![
+            Constraint {
+                coefficients: vec![1.],
+                bound: 1.,
+            },
+            Constraint {
+                coefficients: vec![-1.],
+                bound: -2.,
+            },
+        ];
+        assert_eq!(None, minimize(&[1.], &constraints, &[None], f64::INFINITY));
+    }
+}
@@ -0,0 +1,421 @@
+mod ilp;
+
+use crate::margin::ilp::{minimize, Constraint};
+use crate::model::election::{first_continuing, CandidateId, NormalizedBallot};
+use crate::model::report::IrvMargin;
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet};
+
+/// Largest number of candidates for which the margin is found exactly. The
+/// exact search solves an integer program for every elimination order.
+const MAX_EXACT_CANDIDATES: usize = 5;
+
+/// The candidate a ballot counts for in each round of an elimination order,
+/// as a position in the order, or `None` once the ballot is exhausted.
+type Path = Vec<Option<usize>>;
+
+/// A ballot's rankings, each the candidates marked at it, with the number of
+/// ballots like it.
+type Rankings = (Vec<Vec<CandidateId>>, u32);
+
+fn path(ranking: &[Vec<CandidateId>], order: &[CandidateId]) -> Path {
+    (0..order.len() - 1)
+        .map(|round| {
+            let continuing = &order[round..];
+            first_continuing(ranking, |c| continuing.contains(&c))
+                .map(|c| round + continuing.iter().position(|d| *d == c).unwrap())
+        })
+        .collect()
+}
+
+/// Return the fewest ballots that must change to elect someone other than
+/// `winner`, eliminating the candidates in the given order, or `None` if it
+/// would take at least `cutoff`.
+///
+/// Changing a ballot removes it and adds a ballot with any ranking. Only the
+/// path of each ballot through the order matters, so there is one variable
+/// for the number of removed ballots of each path, and one for the number of
+/// added ballots of each path.
+fn cost_of_order(
+    rankings: &[Rankings],
+    additions: &[Path],
+    order: &[CandidateId],
+    cutoff: u32,
+) -> Option<u32> {
+    let n = order.len();
+    let mut removals: HashMap<Path, u32> = HashMap::new();
+    for (ranking, count) in rankings {
+        *removals.entry(path(ranking, order)).or_default() += count;
+    }
+    let removals: Vec<(Path, u32)> = removals.into_iter().collect();
+    let paths: Vec<&Path> = removals
+        .iter()
+        .map(|(p, _)| p)
+        .chain(additions.iter())
+        .collect();
+    let num_removals = removals.len();
+
+    let mut constraints = Vec::new();
+    for round in 0..n - 1 {
+        let tally = |position: usize| -> f64 {
+            removals
+                .iter()
+                .filter(|(p, _)| p[round] == Some(position))
+                .map(|(_, count)| *count as f64)
+                .sum()
+        };
+
+        // The candidate eliminated in this round must have fewer votes than
+        // every other continuing candidate.
+        for other in round + 1..n {
+            let coefficients = paths
+                .iter()
+                .enumerate()
+                .map(|(j, p)| {
+                    let sign = if j < num_removals { 1. } else { -1. };
+                    if p[round] == Some(other) {
+                        sign
+                    } else if p[round] == Some(round) {
+                        -sign
+                    } else {
+                        0.
+                    }
+                })
+                .collect();
+            constraints.push(Constraint {
+                coefficients,
+                bound: tally(other) - tally(round) - 1.,
+            });
+        }
+    }
+
+    // No more ballots can be added than removed. Removed ballots that are not
+    // replaced become blank.
+    constraints.push(Constraint {
+        coefficients: (0..paths.len())
+            .map(|j| if j < num_removals { -1. } else { 1. })
+            .collect(),
+        bound: 0.,
+    });
+
+    let costs: Vec<f64> = (0..paths.len())
+        .map(|j| if j < num_removals { 1. } else { 0. })
+        .collect();
+    let upper: Vec<Option<f64>> = (0..paths.len())
+        .map(|j| removals.get(j).map(|(_, count)| *count as f64))
+        .collect();
+
+    let solution = minimize(&costs, &constraints, &upper, cutoff as f64)?;
+    Some(solution[..num_removals].iter().sum::<i64>() as u32)
+}
+
+/// Run single-elimination IRV, returning the candidates' votes in each
+/// round, lowest first, and whether any round's last place was tied.
+fn rounds(
+    rankings: &[Rankings],
+    candidates: &[CandidateId],
+) -> (Vec<Vec<(CandidateId, u32)>>, bool) {
+    let mut continuing: HashSet<CandidateId> = candidates.iter().copied().collect();
+    let mut rounds = Vec::new();
+    let mut tied = false;
+
+    while continuing.len() > 1 {
+        let mut votes: HashMap<CandidateId, u32> = continuing.iter().map(|c| (*c, 0)).collect();
+        for (ranking, count) in rankings {
+            if let Some(c) = first_continuing(ranking, |c| continuing.contains(&c)) {
+                *votes.get_mut(&c).unwrap() += count;
+            }
+        }
+
+        let votes: Vec<(CandidateId, u32)> =
+            votes.into_iter().sorted_by_key(|(c, v)| (*v, *c)).collect();
+        tied |= votes[0].1 == votes[1].1;
+        continuing.remove(&votes[0].0);
+        rounds.push(votes);
+    }
+
+    (rounds, tied)
+}
+
+/// Find how many ballots would have to change to elect someone other than
+/// `winner` by single-elimination IRV, counting only candidates with
+/// first-choice votes. Returns `None` if there is only one such candidate,
+/// or if single-elimination IRV doesn't elect `winner`, as when the contest
+/// was decided by a tie break.
+pub fn irv_margin(
+    candidates: &[CandidateId],
+    ballots: &[NormalizedBallot],
+    winner: CandidateId,
+) -> Option<IrvMargin> {
+    let first_choices: HashSet<CandidateId> = ballots
+        .iter()
+        .flat_map(|b| b.choices().first().copied())
+        .collect();
+    let candidates: Vec<CandidateId> = candidates
+        .iter()
+        .copied()
+        .filter(|c| first_choices.contains(c))
+        .collect();
+    if candidates.len() < 2 {
+        return None;
+    }
+
+    let rankings: Vec<Rankings> = ballots
+        .iter()
+        .map(|b| {
+            let ranking = b
+                .rankings()
+                .map(|r| {
+                    r.candidates()
+                        .iter()
+                        .copied()
+                        .filter(|c| first_choices.contains(c))
+                        .collect::<Vec<CandidateId>>()
+                })
+                .filter(|r| !r.is_empty())
+                .collect();
+            (ranking, b.count)
+        })
+        .collect();
+    let (rounds, tied) = rounds(&rankings, &candidates);
+    if rounds.last().unwrap()[1].0 != winner {
+        return None;
+    }
+
+    // Until the elimination order changes, a changed ballot moves at most
+    // one vote away from one candidate and one vote to another. So the order
+    // can't change unless, in some round, enough ballots change to put the
+    // second-last candidate below the last.
+    let lower_bound = rounds
+        .iter()
+        .map(|votes| (votes[1].1 - votes[0].1) / 2 + 1)
+        .min()
+        .unwrap();
+
+    // Moving first choices from the winner to the runner-up lowers only the
+    // winner and raises only the runner-up in every round. Unless the winner
+    // is eliminated sooner, the last round is then between the same two.
+    let last = rounds.last().unwrap();
+    let runner_up = last[0].0;
+    let needed = (last[1].1 - last[0].1) / 2 + 1;
+    let first_round_votes = rounds[0]
+        .iter()
+        .find(|(c, _)| *c == winner)
+        .map_or(0, |(_, votes)| *votes);
+    let mut upper_bound = if !tied && needed <= first_round_votes {
+        Some((needed, runner_up))
+    } else {
+        None
+    };
+
+    let exact = candidates.len() <= MAX_EXACT_CANDIDATES;
+    if exact {
+        // Every ranking an added ballot could have.
+        let all_rankings: Vec<Vec<Vec<CandidateId>>> = (1..=candidates.len())
+            .flat_map(|k| candidates.iter().copied().permutations(k))
+            .map(|ranking| ranking.into_iter().map(|c| vec![c]).collect())
+            .collect();
+
+        for order in candidates.iter().copied().permutations(candidates.len()) {
+            if *order.last().unwrap() == winner {
+                continue;
+            }
+            let cutoff = upper_bound.map_or(u32::MAX, |(cost, _)| cost);
+            if cutoff <= lower_bound {
+                break;
+            }
+
+            let additions: Vec<Path> = all_rankings
+                .iter()
+                .map(|ranking| path(ranking, &order))
+                .unique()
+                .collect();
+            if let Some(cost) = cost_of_order(&rankings, &additions, &order, cutoff) {
+                upper_bound = Some((cost, *order.last().unwrap()));
+            }
+        }
+    }
+
+    Some(IrvMargin {
+        margin: if exact {
+            upper_bound.map(|(cost, _)| cost)
+        } else {
+            None
+        },
+        lower_bound: if exact {
+            upper_bound.map_or(lower_bound, |(cost, _)| cost)
+        } else {
+            lower_bound
+        },
+        upper_bound: upper_bound.map(|(cost, _)| cost),
+        alternate_winner: upper_bound.map(|(_, c)| c),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{BallotEnd, Ranking};
+    use crate::tabulator::tests::ballots;
+    use crate::util::Rng;
+
+    fn candidates(n: u32) -> Vec<CandidateId> {
+        (0..n).map(CandidateId).collect()
+    }
+
+    /// Return the IRV winner if no round has a tie for last place.
+    fn strict_winner(
+        rankings: &[Vec<CandidateId>],
+        candidates: &[CandidateId],
+    ) -> Option<CandidateId> {
+        let weighted: Vec<Rankings> = rankings
+            .iter()
+            .map(|r| (r.iter().map(|c| vec![*c]).collect(), 1))
+            .collect();
+        match rounds(&weighted, candidates) {
+            (_, true) => None,
+            (rounds, false) => Some(rounds.last().unwrap()[1].0),
+        }
+    }
+
+    /// Find the margin by trying every way of changing up to `max` ballots.
+    fn brute_force_margin(
+        rankings: &[Vec<CandidateId>],
+        candidates: &[CandidateId],
+        max: usize,
+    ) -> Option<u32> {
+        let winner = strict_winner(rankings, candidates).unwrap();
+        let options: Vec<Vec<CandidateId>> = (0..=candidates.len())
+            .flat_map(|k| candidates.iter().copied().permutations(k))
+            .collect();
+
+        for k in 1..=max {
+            for changed in (0..rankings.len()).combinations(k) {
+                for replacements in (0..k).map(|_| options.iter()).multi_cartesian_product() {
+                    let mut rankings = rankings.to_vec();
+                    for (i, replacement) in changed.iter().zip(replacements) {
+                        rankings[*i] = replacement.clone();
+                    }
+                    match strict_winner(&rankings, candidates) {
+                        Some(w) if w != winner => return Some(k as u32),
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    #[test]
+    fn test_two_candidates() {
+        let ballots = ballots(&[(60, &[0]), (40, &[1])]);
+        let margin = irv_margin(&candidates(2), &ballots, CandidateId(0)).unwrap();
+
+        assert_eq!(Some(11), margin.margin);
+        assert_eq!(11, margin.lower_bound);
+        assert_eq!(Some(CandidateId(1)), margin.alternate_winner);
+    }
+
+    #[test]
+    fn test_earlier_round_margin() {
+        // C is eliminated first and their votes elect B, 60 to 40. Moving 11
+        // votes from B to A would change the last round, but moving 6 from B
+        // to C eliminates B instead, and A then beats C.
+        let ballots = ballots(&[(40, &[0]), (35, &[1]), (25, &[2, 1])]);
+        let margin = irv_margin(&candidates(3), &ballots, CandidateId(1)).unwrap();
+
+        assert_eq!(Some(6), margin.margin);
+        assert_eq!(Some(6), margin.upper_bound);
+        assert_eq!(Some(CandidateId(0)), margin.alternate_winner);
+    }
+
+    #[test]
+    fn test_other_winner() {
+        let ballots = ballots(&[(40, &[0]), (35, &[1]), (25, &[2, 1])]);
+        assert_eq!(None, irv_margin(&candidates(3), &ballots, CandidateId(0)));
+    }
+
+    #[test]
+    fn test_overvote_resolved() {
+        // Candidate 3 has no first choices, so an overvote of 0 and 3 counts
+        // for 0 just as a ranking of 0 alone would.
+        let mut overvoted = ballots(&[(40, &[0]), (38, &[1]), (25, &[2, 1]), (10, &[2])]);
+        overvoted[3] = NormalizedBallot::from_rankings(
+            "3".to_string(),
+            vec![
+                Ranking::Candidate(CandidateId(2)),
+                Ranking::Overvote(vec![CandidateId(0), CandidateId(3)]),
+            ],
+            BallotEnd::NoMoreRankings,
+        )
+        .with_count(10);
+        let ranked = ballots(&[(40, &[0]), (38, &[1]), (25, &[2, 1]), (10, &[2, 0])]);
+
+        let winner = CandidateId(1);
+        assert_eq!(
+            irv_margin(&candidates(4), &ranked, winner),
+            irv_margin(&candidates(4), &overvoted, winner)
+        );
+        assert!(irv_margin(&candidates(4), &ranked, winner).is_some());
+    }
+
+    #[test]
+    fn test_bounds_only() {
+        let spec: Vec<(u32, &[u32])> = vec![
+            (40, &[0]),
+            (25, &[1]),
+            (15, &[2]),
+            (12, &[3, 1]),
+            (10, &[4, 2]),
+            (8, &[5, 0]),
+        ];
+        let ballots = ballots(&spec);
+        let margin = irv_margin(&candidates(6), &ballots, CandidateId(0)).unwrap();
+
+        assert_eq!(None, margin.margin);
+        // Candidates 4 and 5 are 2 votes apart in the first round.
+        assert_eq!(2, margin.lower_bound);
+        // The last round is 48 to 37.
+        assert_eq!(Some(6), margin.upper_bound);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(18);
+        let candidates = candidates(3);
+        let orders: Vec<Vec<CandidateId>> = (1..=3)
+            .flat_map(|k| candidates.iter().copied().permutations(k))
+            .collect();
+
+        let mut tested = 0;
+        while tested < 10 {
+            let rankings: Vec<Vec<CandidateId>> = (0..7)
+                .map(|_| orders[rng.below(orders.len() as u64) as usize].clone())
+                .collect();
+            let firsts: HashSet<CandidateId> = rankings.iter().map(|r| r[0]).collect();
+            let winner = match strict_winner(&rankings, &candidates) {
+                Some(winner) if firsts.len() == 3 => winner,
+                _ => continue,
+            };
+
+            let ballots: Vec<NormalizedBallot> = rankings
+                .iter()
+                .map(|r| {
+                    let r: Vec<u32> = r.iter().map(|c| c.0).collect();
+                    ballots(&[(1, &r)]).pop().unwrap()
+                })
+                .collect();
+            let margin = irv_margin(&candidates, &ballots, winner)
+                .unwrap()
+                .margin
+                .unwrap();
+
+            match brute_force_margin(&rankings, &candidates, 3) {
+                Some(expected) => assert_eq!(expected, margin),
+                None => assert!(margin > 3),
+            }
+            tested += 1;
+        }
+    }
+}
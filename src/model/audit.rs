@@ -0,0 +1,46 @@
+use crate::model::election::CandidateId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum AssertionKind {
+    /// `winner` has more first choices than `loser` has ballots ranking them
+    /// above `winner`, so `winner` cannot be eliminated before `loser`.
+    WinnerOnly,
+    /// With only the `continuing` candidates left, `winner` has more votes
+    /// than `loser`, so `winner` is not the next candidate eliminated.
+    IrvElimination,
+}
+
+/// A claim about the ballots which, if it holds, rules out some of the
+/// elimination orders that would elect a different candidate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Assertion {
+    pub kind: AssertionKind,
+    pub winner: CandidateId,
+    pub loser: CandidateId,
+    /// Candidates left in the count, for IRV elimination assertions.
+    #[serde(default)]
+    pub continuing: Vec<CandidateId>,
+    /// Number of votes by which the assertion holds.
+    pub margin: u32,
+    /// The margin as a fraction of all ballots in the contest.
+    pub diluted_margin: f64,
+}
+
+/// RAIRE assertions that together confirm the winner of a single-seat IRV
+/// contest. An audit that checks every assertion checks the outcome.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContestAssertions {
+    pub winner: CandidateId,
+    /// Candidates with any first-choice votes, which are the ones the
+    /// assertions consider.
+    pub candidates: Vec<CandidateId>,
+    pub total_ballots: u32,
+    /// Inverse of the smallest diluted margin. The sample an audit needs
+    /// grows with it.
+    pub difficulty: f64,
+    pub assertions: Vec<Assertion>,
+}
@@ -1,7 +1,10 @@
 use crate::model::metadata::TabulationOptions;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::hash_map::Entry;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 
 #[derive(Clone, Copy, Debug, PartialEq, Ord, PartialOrd, Eq, Hash)]
@@ -53,6 +56,9 @@ pub enum CandidateType {
 pub struct Candidate {
     pub name: String,
     pub candidate_type: CandidateType,
+    /// The candidate's identifier in the raw ballot data, if it has one.
+    #[serde(default)]
+    pub external_id: Option<String>,
 }
 
 impl Candidate {
@@ -60,6 +66,7 @@ impl Candidate {
         Candidate {
             name,
             candidate_type,
+            external_id: None,
         }
     }
 }
@@ -75,46 +82,314 @@ pub enum Choice {
 pub struct Ballot {
     pub id: String,
     pub choices: Vec<Choice>,
+
+    /// Number of identical ballots this ballot stands for. Formats that
+    /// aggregate identical ballots report them once with their count.
+    pub count: u32,
+
+    /// Candidates marked at each overvoted ranking, by index in `choices`,
+    /// for formats that record them.
+    pub overvotes: BTreeMap<usize, Vec<CandidateId>>,
+
+    pub source: BallotSource,
 }
 
 impl Ballot {
     pub fn new(id: String, choices: Vec<Choice>) -> Ballot {
-        Ballot { id, choices }
+        Ballot::with_count(id, choices, 1)
+    }
+
+    pub fn with_count(id: String, choices: Vec<Choice>, count: u32) -> Ballot {
+        Ballot {
+            id,
+            choices,
+            count,
+            overvotes: BTreeMap::new(),
+            source: BallotSource::default(),
+        }
+    }
+
+    pub fn with_overvotes(mut self, overvotes: BTreeMap<usize, Vec<CandidateId>>) -> Ballot {
+        self.overvotes = overvotes;
+        self
+    }
+
+    pub fn with_source(mut self, source: BallotSource) -> Ballot {
+        self.source = source;
+        self
     }
 }
 
+/// How a ballot was cast.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CountingGroup {
+    ElectionDay,
+    VoteByMail,
+    Provisional,
+    /// A group the ballot data names that isn't one of the above.
+    Other(String),
+}
+
+/// Where and how a ballot was cast and counted, as far as the ballot data
+/// records it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotSource {
+    /// Name of the precinct, or its id where the ballot data doesn't name it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub precinct: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counting_group: Option<CountingGroup>,
+    /// Batch the ballot was scanned in, as named by the ballot data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch: Option<String>,
+}
+
+impl BallotSource {
+    /// Forget whatever `other` doesn't share, so that a ballot standing for
+    /// several ballots only claims what is true of all of them.
+    fn retain_common(&mut self, other: &BallotSource) {
+        if self.precinct != other.precinct {
+            self.precinct = None;
+        }
+        if self.counting_group != other.counting_group {
+            self.counting_group = None;
+        }
+        if self.batch != other.batch {
+            self.batch = None;
+        }
+    }
+}
+
+/// One ranking of a normalized ballot.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Ranking {
+    Candidate(CandidateId),
+    /// An overvoted ranking, kept so that tabulation can resolve it once it
+    /// knows which of its candidates are continuing.
+    Overvote(Vec<CandidateId>),
+}
+
+impl Ranking {
+    /// The candidates marked at this ranking.
+    pub fn candidates(&self) -> &[CandidateId] {
+        match self {
+            Ranking::Candidate(c) => std::slice::from_ref(c),
+            Ranking::Overvote(candidates) => candidates,
+        }
+    }
+}
+
+/// Return the candidate that a ballot with the given rankings counts for,
+/// given which candidates are continuing. Rankings with no continuing
+/// candidates are passed over, and a ranking with two or more is an
+/// overvote that exhausts the ballot.
+pub fn first_continuing<T: Copy>(rankings: &[Vec<T>], continuing: impl Fn(T) -> bool) -> Option<T> {
+    for ranking in rankings {
+        let mut marked = ranking.iter().copied().filter(|c| continuing(*c));
+        match (marked.next(), marked.next()) {
+            (None, _) => (),
+            (Some(c), None) => return Some(c),
+            (Some(_), Some(_)) => return None,
+        }
+    }
+    None
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+/// Why a normalized ballot's list of choices ends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BallotEnd {
+    /// The voter ranked no further candidates.
+    NoMoreRankings,
+    /// The next ranking was an overvote.
+    Overvote,
+    /// The voter skipped too many rankings in a row before ranking another
+    /// candidate.
+    SkippedRankings,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[serde(from = "NormalizedBallotData")]
 pub struct NormalizedBallot {
     pub id: String,
-    choices: VecDeque<CandidateId>,
-    pub overvoted: bool,
+    choices: VecDeque<Ranking>,
+    pub end: BallotEnd,
+
+    /// Number of identical ballots this ballot stands for.
+    pub count: u32,
+
+    #[serde(flatten)]
+    pub source: BallotSource,
+}
+
+/// Serialized form of a `NormalizedBallot`, which also accepts files
+/// written before ballots recorded a count or why their list ended.
+#[derive(Deserialize)]
+struct NormalizedBallotData {
+    id: String,
+    choices: VecDeque<Ranking>,
+    #[serde(default)]
+    end: Option<BallotEnd>,
+    #[serde(default)]
+    overvoted: bool,
+    #[serde(default = "default_count")]
+    count: u32,
+    #[serde(flatten)]
+    source: BallotSource,
+}
+
+impl From<NormalizedBallotData> for NormalizedBallot {
+    fn from(data: NormalizedBallotData) -> NormalizedBallot {
+        let end = data.end.unwrap_or(if data.overvoted {
+            BallotEnd::Overvote
+        } else {
+            BallotEnd::NoMoreRankings
+        });
+
+        NormalizedBallot {
+            id: data.id,
+            choices: data.choices,
+            end,
+            count: data.count,
+            source: data.source,
+        }
+    }
 }
 
 impl NormalizedBallot {
-    pub fn new(id: String, choices: Vec<CandidateId>, overvoted: bool) -> NormalizedBallot {
+    #[allow(unused)]
+    pub fn new(id: String, choices: Vec<CandidateId>, end: BallotEnd) -> NormalizedBallot {
+        let rankings = choices.into_iter().map(Ranking::Candidate).collect();
+        NormalizedBallot::from_rankings(id, rankings, end)
+    }
+
+    pub fn from_rankings(id: String, rankings: Vec<Ranking>, end: BallotEnd) -> NormalizedBallot {
         NormalizedBallot {
             id,
-            choices: choices.into(),
-            overvoted,
+            choices: rankings.into(),
+            end,
+            count: 1,
+            source: BallotSource::default(),
         }
     }
 
-    #[allow(unused)]
+    /// Return a copy of this ballot standing for `count` ballots.
+    pub fn with_count(&self, count: u32) -> NormalizedBallot {
+        NormalizedBallot {
+            count,
+            ..self.clone()
+        }
+    }
+
+    pub fn rankings(&self) -> impl Iterator<Item = &Ranking> {
+        self.choices.iter()
+    }
+
+    /// Return every candidate the ballot marks, including those marked at
+    /// overvoted rankings, in the order they are first marked.
+    pub fn ranked_candidates(&self) -> Vec<CandidateId> {
+        let mut ranked: Vec<CandidateId> = Vec::new();
+        for c in self.choices.iter().flat_map(|r| r.candidates()) {
+            if !ranked.contains(c) {
+                ranked.push(*c);
+            }
+        }
+        ranked
+    }
+
+    /// Read the ballot as though only the candidates for which `continuing`
+    /// returns true were running, the way tabulation reads it once the
+    /// others are eliminated. Returns the candidates in the order the ballot
+    /// ranks them, and why the list ends.
+    fn read_among(
+        &self,
+        continuing: impl Fn(CandidateId) -> bool,
+    ) -> (Vec<CandidateId>, BallotEnd) {
+        let mut ranking: Vec<CandidateId> = Vec::new();
+        for r in &self.choices {
+            let mut marked = r.candidates().iter().copied().filter(|c| continuing(*c));
+            match (marked.next(), marked.next()) {
+                (None, _) => (),
+                (Some(c), None) => {
+                    if !ranking.contains(&c) {
+                        ranking.push(c);
+                    }
+                }
+                (Some(_), Some(_)) => return (ranking, BallotEnd::Overvote),
+            }
+        }
+        (ranking, self.end)
+    }
+
+    /// Return the candidates the ballot ranks among those for which
+    /// `continuing` returns true, in order. An overvoted ranking counts for
+    /// its only continuing candidate, is passed over if it has none, and ends
+    /// the list if it has two or more, as in tabulation.
+    pub fn ranking_among(&self, continuing: impl Fn(CandidateId) -> bool) -> Vec<CandidateId> {
+        self.read_among(continuing).0
+    }
+
+    /// Return why the ballot's list of candidates ends when only those for
+    /// which `continuing` returns true are running.
+    pub fn end_among(&self, continuing: impl Fn(CandidateId) -> bool) -> BallotEnd {
+        self.read_among(continuing).1
+    }
+
+    /// Return the ranked candidates, with every candidate continuing. An
+    /// overvoted ranking of two or more candidates ends the list.
     pub fn choices(&self) -> Vec<CandidateId> {
-        self.choices.clone().into()
+        self.ranking_among(|_| true)
     }
 
     pub fn top_vote(&self) -> Choice {
         match self.choices.front() {
-            Some(v) => Choice::Vote(*v),
-            None => {
-                if self.overvoted {
-                    Choice::Overvote
-                } else {
-                    Choice::Undervote
+            Some(Ranking::Candidate(c)) => Choice::Vote(*c),
+            Some(Ranking::Overvote(_)) => Choice::Overvote,
+            None => match self.end {
+                BallotEnd::Overvote => Choice::Overvote,
+                BallotEnd::NoMoreRankings | BallotEnd::SkippedRankings => Choice::Undervote,
+            },
+        }
+    }
+
+    /// Return the top choice given the candidates no longer continuing, or
+    /// `None` if the top ranking should be passed over. An overvoted ranking
+    /// counts for its only continuing candidate, and is an overvote if two
+    /// or more of its candidates are continuing.
+    pub fn continuing_vote(&self, eliminated: &HashSet<CandidateId>) -> Option<Choice> {
+        match self.choices.front() {
+            Some(Ranking::Candidate(c)) if eliminated.contains(c) => None,
+            Some(Ranking::Overvote(candidates)) => {
+                let mut continuing = candidates.iter().filter(|c| !eliminated.contains(c));
+                match (continuing.next(), continuing.next()) {
+                    (None, _) => None,
+                    (Some(c), None) => Some(Choice::Vote(*c)),
+                    (Some(_), Some(_)) => Some(Choice::Overvote),
                 }
             }
+            _ => Some(self.top_vote()),
+        }
+    }
+
+    /// Remove rankings of the given candidates, as though they were not on
+    /// the ballot.
+    pub fn remove_candidates(&mut self, candidates: &HashSet<CandidateId>) {
+        for ranking in &mut self.choices {
+            if let Ranking::Overvote(overvoted) = ranking {
+                overvoted.retain(|c| !candidates.contains(c));
+            }
         }
+        self.choices.retain(|r| match r {
+            Ranking::Candidate(c) => !candidates.contains(c),
+            Ranking::Overvote(overvoted) => !overvoted.is_empty(),
+        });
     }
 
     pub fn pop_top_vote(mut self) -> Self {
@@ -126,6 +401,9 @@ impl NormalizedBallot {
 pub struct Election {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<Ballot>,
+    /// Number of seats, for formats that record it. Contest metadata
+    /// overrides it.
+    pub seats: Option<u32>,
 }
 
 impl Election {
@@ -133,14 +411,51 @@ impl Election {
         Election {
             candidates,
             ballots,
+            seats: None,
         }
     }
+
+    pub fn with_seats(mut self, seats: u32) -> Election {
+        self.seats = Some(seats);
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct NormalizedElection {
     pub candidates: Vec<Candidate>,
     pub ballots: Vec<NormalizedBallot>,
+    /// Candidates who withdrew from the contest. They have been removed from
+    /// every ballot.
+    #[serde(default)]
+    pub withdrawn: Vec<CandidateId>,
+}
+
+impl NormalizedElection {
+    /// Merge ballots with identical rankings into a single weighted ballot,
+    /// in the order each ranking first appears. A merged ballot keeps the id
+    /// of the first ballot in its group, and the parts of its source that
+    /// every ballot in the group shares.
+    pub fn grouped_ballots(&self) -> Vec<NormalizedBallot> {
+        let mut index: HashMap<(&VecDeque<Ranking>, BallotEnd), usize> = HashMap::new();
+        let mut grouped: Vec<NormalizedBallot> = Vec::new();
+
+        for ballot in &self.ballots {
+            match index.entry((&ballot.choices, ballot.end)) {
+                Entry::Occupied(entry) => {
+                    let merged = &mut grouped[*entry.get()];
+                    merged.count += ballot.count;
+                    merged.source.retain_common(&ballot.source);
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(grouped.len());
+                    grouped.push(ballot.clone());
+                }
+            }
+        }
+
+        grouped
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
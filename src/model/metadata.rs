@@ -1,3 +1,4 @@
+use crate::tabulator::get_tabulation_method;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -38,7 +39,13 @@ pub struct ElectionMetadata {
 
     pub tabulation_options: Option<TabulationOptions>,
 
-    pub normalization: String,
+    /// How to read each ballot's rankings: either the name of a rule set,
+    /// `simple` or `maine`, or the rules themselves.
+    pub normalization: Normalization,
+
+    /// How to treat votes for unqualified write-in candidates. Defaults to
+    /// keeping them.
+    pub write_ins: Option<WriteInPolicy>,
 
     pub contests: Vec<Contest>,
 
@@ -47,15 +54,244 @@ pub struct ElectionMetadata {
     pub website: Option<String>,
 }
 
+/// How to treat votes for unqualified write-in candidates.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WriteInPolicy {
+    /// Keep each write-in candidate as read from the ballot data.
+    Keep,
+    /// Count every write-in as a vote for a single "Write-in" candidate.
+    Merge,
+    /// Treat write-in votes as undervotes.
+    Drop,
+}
+
+/// Rules for reading each ballot's rankings, given by name or in full.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Normalization {
+    Named(String),
+    Rules(NormalizationRules),
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationRules {
+    /// How to treat a ranking with more than one candidate. Defaults to
+    /// exhausting the ballot.
+    pub overvote: Option<OvervoteRule>,
+    /// Most skipped rankings in a row a ballot may have before ranking
+    /// another candidate. A ballot that skips more is exhausted. Defaults to
+    /// no limit.
+    pub max_skipped_rankings: Option<u32>,
+    /// How to treat a later ranking of a candidate already ranked. Defaults
+    /// to ignoring it.
+    pub duplicates: Option<DuplicateRule>,
+    /// Number of rankings counted, with later rankings ignored. Defaults to
+    /// counting every ranking.
+    pub max_ranks: Option<u32>,
+}
+
+/// How to treat an overvoted ranking.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum OvervoteRule {
+    /// The ballot is exhausted at the overvote.
+    Exhaust,
+    /// The overvoted ranking is passed over and the next ranking counts.
+    SkipRank,
+    /// The ballot is exhausted at the overvote only if it involves two or
+    /// more continuing candidates. Otherwise, it counts for the one
+    /// continuing candidate, or is passed over if there are none.
+    ExhaustIfMultipleContinuing,
+}
+
+/// How to treat a later ranking of a candidate the ballot already ranked.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DuplicateRule {
+    /// The ranking is passed over.
+    Ignore,
+    /// The ranking is treated as skipped.
+    Skip,
+}
+
+/// Rule for choosing which candidates to eliminate at the end of a round.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum EliminationMode {
+    /// Eliminate only the last-place candidate.
+    Single,
+    /// Eliminate every trailing candidate whose combined votes are fewer than
+    /// the votes of the next candidate up, since none of them could win.
+    Batch,
+    /// Eliminate every candidate with fewer than the given number of votes,
+    /// or the last-place candidate if there are none.
+    Threshold(u32),
+    /// Compare the bottom two candidates head-to-head on the ballots'
+    /// rankings and eliminate the one fewer voters prefer.
+    BottomTwoRunoff,
+}
+
+/// Rule for when to stop eliminating candidates in a single-seat contest.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum StoppingRule {
+    /// Stop as soon as a candidate has a majority of continuing ballots.
+    Majority,
+    /// Keep eliminating until two candidates remain, even if a candidate
+    /// already has a majority.
+    TwoCandidates,
+    /// Keep eliminating until only the winner remains.
+    OneCandidate,
+}
+
+/// Rule for breaking a tie between candidates with equal votes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TieBreakRule {
+    /// The candidate with fewer votes in the earliest round in which the tied
+    /// candidates' votes differ loses.
+    PreviousRoundsForward,
+    /// The candidate with fewer votes in the most recent round in which the
+    /// tied candidates' votes differ loses.
+    PreviousRoundsBackward,
+    /// Candidates are put in a random order drawn from the given seed, and the
+    /// candidate latest in that order loses.
+    Lottery(u64),
+    /// Names of candidates in the order drawn by lot. The candidate latest in
+    /// the order loses.
+    LotOrder(Vec<String>),
+}
+
+/// How a Borda count scores candidates that a ballot leaves unranked.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum BordaTruncation {
+    /// Ranked candidates score as if the ballot were complete, and unranked
+    /// candidates score nothing.
+    Pessimistic,
+    /// Ranked candidates score as if the ballot were complete, and unranked
+    /// candidates share the remaining points equally.
+    Averaged,
+    /// A ballot ranking k candidates gives k points to its first choice down
+    /// to 1 point to its last, and nothing to unranked candidates.
+    Modified,
+}
+
+/// How to fill the seats of a contest with more than one seat.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum MultiSeatMethod {
+    /// Single transferable vote with the Droop quota.
+    Stv,
+    /// Run IRV once per seat, removing each winner before the next run.
+    SequentialIrv,
+    /// Meek's method of single transferable vote, with fractional transfers.
+    Meek,
+    /// Warren's method of single transferable vote, with fractional
+    /// transfers.
+    Warren,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TabulationOptions {
+    /// Legacy switch for batch elimination, used when `elimination` is not given.
     pub eager: Option<bool>,
+
+    pub elimination: Option<EliminationMode>,
+
+    /// When to stop tabulating. Defaults to stopping when two candidates remain.
+    pub stopping_rule: Option<StoppingRule>,
+
+    /// Rules for breaking ties, applied in order until one resolves the tie.
+    /// Ties that no rule resolves go against the candidate with the highest id.
+    pub tie_break: Option<Vec<TieBreakRule>>,
+
+    /// Number of seats to fill.
+    pub seats: Option<u32>,
+
+    /// Deprecated alias for `method` in contests with more than one seat,
+    /// used when `method` is not given.
+    pub multi_seat: Option<MultiSeatMethod>,
+
+    /// How the Borda-based alternative methods score truncated ballots.
+    /// Defaults to pessimistic.
+    pub borda_truncation: Option<BordaTruncation>,
+
+    /// Name of the tabulation method, e.g. `irv` or `meek`. Defaults to
+    /// `irv` for a single seat, or to `stv` for more than one.
+    pub method: Option<String>,
 }
 
 impl Default for TabulationOptions {
     fn default() -> Self {
-        TabulationOptions { eager: Some(true) }
+        TabulationOptions {
+            eager: Some(true),
+            elimination: None,
+            stopping_rule: None,
+            tie_break: None,
+            seats: None,
+            multi_seat: None,
+            borda_truncation: None,
+            method: None,
+        }
+    }
+}
+
+impl TabulationOptions {
+    pub fn elimination_mode(&self) -> EliminationMode {
+        match (self.elimination, self.eager) {
+            (Some(mode), _) => mode,
+            (None, Some(false)) => EliminationMode::Single,
+            (None, _) => EliminationMode::Batch,
+        }
+    }
+
+    /// Number of seats to fill, which defaults to one. Panics if `seats` is
+    /// zero.
+    pub fn seats(&self) -> u32 {
+        match self.seats {
+            Some(0) => panic!("A contest should have at least one seat."),
+            seats => seats.unwrap_or(1),
+        }
+    }
+
+    /// Whether the contest is counted by IRV eliminating candidates only in
+    /// ways that can't change the winner, which margin and audit
+    /// calculations assume.
+    pub fn is_standard_irv(&self) -> bool {
+        self.tabulation_method() == "irv"
+            && matches!(
+                self.elimination_mode(),
+                EliminationMode::Single | EliminationMode::Batch
+            )
+    }
+
+    /// Name of the tabulation method. Panics if the method is not
+    /// implemented, or doesn't fill the contest's number of seats.
+    pub fn tabulation_method(&self) -> &str {
+        let seats = self.seats();
+        let method = match &self.method {
+            Some(method) => method.as_str(),
+            None if seats == 1 => "irv",
+            None => match self.multi_seat.unwrap_or(MultiSeatMethod::Stv) {
+                MultiSeatMethod::Stv => "stv",
+                MultiSeatMethod::SequentialIrv => "sequentialIrv",
+                MultiSeatMethod::Meek => "meek",
+                MultiSeatMethod::Warren => "warren",
+            },
+        };
+
+        match (get_tabulation_method(method).multi_seat(), seats) {
+            (true, 1) => panic!("The {} method should fill more than one seat.", method),
+            (false, seats) if seats > 1 => panic!(
+                "The {} method fills one seat, but the contest has {}.",
+                method, seats
+            ),
+            _ => method,
+        }
     }
 }
 
@@ -64,4 +300,11 @@ impl Default for TabulationOptions {
 pub struct Contest {
     pub office: String,
     pub loader_params: Option<BTreeMap<String, String>>,
+
+    /// Number of seats to fill, overriding the election's tabulation options.
+    pub seats: Option<u32>,
+
+    /// Candidates who withdrew after the ballots were printed, by name or by
+    /// their identifier in the raw ballot data. Rankings of them are skipped.
+    pub withdrawn: Option<Vec<String>>,
 }
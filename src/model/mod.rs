@@ -1,3 +1,5 @@
+pub mod audit;
 pub mod election;
 pub mod metadata;
+pub mod normalization;
 pub mod report;
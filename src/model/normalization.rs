@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A normalization rule that changed how a ballot's rankings were read.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum NormalizationAction {
+    /// A later ranking of a candidate already ranked was not counted.
+    Duplicate,
+    /// A skipped ranking was passed over to read the rankings after it.
+    SkippedRanking,
+    /// Too many skipped rankings in a row exhausted the ballot.
+    SkippedRankingsExhausted,
+    /// An overvote exhausted the ballot.
+    OvervoteExhausted,
+    /// An overvoted ranking was passed over.
+    OvervoteSkipped,
+    /// An overvoted ranking was kept for tabulation to resolve.
+    OvervoteKept,
+    /// Rankings past the number counted were ignored.
+    RanksIgnored,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationEvent {
+    /// Rank the rule applied at, counting from 1.
+    pub rank: u32,
+    pub action: NormalizationAction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BallotNormalization {
+    pub id: String,
+    pub events: Vec<NormalizationEvent>,
+}
+
+/// Record of the normalization rules applied to each ballot of a contest.
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizationTrail {
+    /// Number of ballots each action applied to. A ballot is counted once
+    /// per time an action applied to it.
+    pub counts: BTreeMap<NormalizationAction, u32>,
+    /// The actions applied to each ballot, for ballots with any.
+    pub ballots: Vec<BallotNormalization>,
+}
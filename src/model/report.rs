@@ -1,6 +1,7 @@
 use crate::model::election::{Candidate, CandidateId, ElectionInfo};
 use crate::tabulator::{Allocatee, TabulatorRound};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +26,8 @@ pub struct ContestIndexEntry {
     pub office_name: String,
     pub name: String,
     pub winner: String,
+    /// Names of all elected candidates, in the order they were elected.
+    pub winners: Vec<String>,
     pub num_candidates: u32,
     pub num_rounds: u32,
 }
@@ -64,6 +67,82 @@ pub struct CandidatePairTable {
     pub entries: Vec<Vec<Option<CandidatePairEntry>>>,
 }
 
+/// Outcome of a ranked voting method other than the one used to tabulate the
+/// contest.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodResult {
+    /// The winner, unless several candidates are tied for first place.
+    pub winner: Option<CandidateId>,
+    /// Candidates from first to last place. Candidates in the same inner
+    /// list are tied.
+    pub ranking: Vec<Vec<CandidateId>>,
+}
+
+impl MethodResult {
+    pub fn from_ranking(ranking: Vec<Vec<CandidateId>>) -> MethodResult {
+        let winner = match ranking.first().map(|d| d.as_slice()) {
+            Some([winner]) => Some(*winner),
+            _ => None,
+        };
+
+        MethodResult { winner, ranking }
+    }
+}
+
+/// A pairwise contest between two candidates that was not tied.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairwiseEdge {
+    pub winner: CandidateId,
+    pub loser: CandidateId,
+    /// Number of ballots preferring the winner, less the number preferring the loser.
+    pub margin: u32,
+    /// Number of ballots preferring the winner.
+    pub strength: u32,
+}
+
+/// A pairwise contest between two candidates that was tied.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairwiseTie {
+    pub candidates: Vec<CandidateId>,
+    /// Number of ballots preferring each candidate.
+    pub votes: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PairwiseGraph {
+    /// Pairwise wins, in descending order of margin.
+    pub edges: Vec<PairwiseEdge>,
+    pub ties: Vec<PairwiseTie>,
+}
+
+/// How many ballots would have to change to elect a different candidate in a
+/// single-seat IRV contest without breaking any ties, where a changed ballot
+/// may take any ranking.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IrvMargin {
+    /// The margin, if it was found exactly. It is only searched for in
+    /// contests with few candidates.
+    pub margin: Option<u32>,
+    /// Changing fewer ballots than this cannot change the winner.
+    pub lower_bound: u32,
+    /// Changing this many ballots is enough to elect `alternate_winner`.
+    pub upper_bound: Option<u32>,
+    pub alternate_winner: Option<CandidateId>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CondorcetMethods {
+    pub ranked_pairs: MethodResult,
+    pub schulze: MethodResult,
+    pub minimax: MethodResult,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ContestReport {
@@ -71,7 +150,19 @@ pub struct ContestReport {
     pub ballot_count: u32,
     pub candidates: Vec<Candidate>,
     pub rounds: Vec<TabulatorRound>,
+    /// Rounds of each seat's tabulation, for contests filled by sequential
+    /// IRV. `rounds` holds the first seat's rounds.
+    #[serde(default)]
+    pub seat_rounds: Vec<Vec<TabulatorRound>>,
     pub winner: CandidateId,
+    /// All elected candidates in the order they were elected. For single-seat
+    /// contests, this is just `winner`.
+    #[serde(default)]
+    pub winners: Vec<CandidateId>,
+    /// Candidates who withdrew before tabulation. Rankings of them were
+    /// skipped, so they appear in no round.
+    #[serde(default)]
+    pub withdrawn: Vec<CandidateId>,
     pub condorcet: Option<CandidateId>,
     pub num_candidates: u32,
     pub total_votes: Vec<CandidateVotes>,
@@ -79,10 +170,44 @@ pub struct ContestReport {
     pub first_alternate: CandidatePairTable,
     pub first_final: CandidatePairTable,
     pub smith_set: Vec<CandidateId>,
+    #[serde(default)]
+    pub schwartz_set: Vec<CandidateId>,
+    #[serde(default)]
+    pub pairwise_graph: Option<PairwiseGraph>,
+    #[serde(default)]
+    pub condorcet_methods: Option<CondorcetMethods>,
+    /// Results of other ranked-ballot methods, by method name.
+    #[serde(default)]
+    pub alternative_methods: Option<BTreeMap<String, MethodResult>>,
+    /// Margin of victory, for single-seat IRV contests.
+    #[serde(default)]
+    pub margin: Option<IrvMargin>,
 }
 
 impl ContestReport {
     pub fn winner(&self) -> &Candidate {
         &self.candidates[self.winner.0 as usize]
     }
+
+    /// Return all elected candidates, in the order they were elected.
+    pub fn elected(&self) -> Vec<&Candidate> {
+        if self.winners.is_empty() {
+            // Reports written before multi-seat contests only have `winner`.
+            vec![self.winner()]
+        } else {
+            self.winners
+                .iter()
+                .map(|c| &self.candidates[c.0 as usize])
+                .collect()
+        }
+    }
+
+    /// Return the number of rounds tabulated, across all seats.
+    pub fn num_rounds(&self) -> usize {
+        if self.seat_rounds.is_empty() {
+            self.rounds.len()
+        } else {
+            self.seat_rounds.iter().map(|rounds| rounds.len()).sum()
+        }
+    }
 }
@@ -0,0 +1,121 @@
+//! Runs the normalization cases kept next to the statutes they come from.
+//!
+//! Every `normalization.json` file under `statutes/` names a normalization,
+//! the statute its cases illustrate, and a list of cases. Each case gives a
+//! ballot's raw rankings and the rankings it should normalize to. Rankings
+//! are written as candidate numbers, `"undervote"`, `"overvote"`, or a list
+//! of candidate numbers for an overvote whose candidates are known.
+
+use crate::model::election::{Ballot, BallotEnd, CandidateId, Choice, NormalizedBallot, Ranking};
+use crate::model::metadata::Normalization;
+use crate::normalizers::get_normalization_rules;
+use crate::normalizers::rules::normalize_ballot;
+use crate::util::{get_files_from_path, read_serialized};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CaseFile {
+    normalization: Normalization,
+    /// File name of the statute, in the same directory.
+    statute: String,
+    cases: Vec<Case>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Case {
+    description: String,
+    /// Section of the statute the case illustrates.
+    #[serde(default)]
+    section: Option<String>,
+    ballot: Vec<RawChoice>,
+    choices: Vec<Ranking>,
+    #[serde(default = "no_more_rankings")]
+    end: BallotEnd,
+}
+
+fn no_more_rankings() -> BallotEnd {
+    BallotEnd::NoMoreRankings
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawChoice {
+    Vote(CandidateId),
+    Overvote(Vec<CandidateId>),
+    Mark(Mark),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum Mark {
+    Undervote,
+    Overvote,
+}
+
+fn raw_ballot(choices: &[RawChoice]) -> Ballot {
+    let mut overvotes = BTreeMap::new();
+    let choices = choices
+        .iter()
+        .enumerate()
+        .map(|(rank, choice)| match choice {
+            RawChoice::Vote(c) => Choice::Vote(*c),
+            RawChoice::Overvote(candidates) => {
+                overvotes.insert(rank, candidates.clone());
+                Choice::Overvote
+            }
+            RawChoice::Mark(Mark::Undervote) => Choice::Undervote,
+            RawChoice::Mark(Mark::Overvote) => Choice::Overvote,
+        })
+        .collect();
+    Ballot::new("case".into(), choices).with_overvotes(overvotes)
+}
+
+/// Run the cases of one file, returning a description of each failure.
+fn run_case_file(path: &Path) -> Vec<String> {
+    let file: CaseFile = read_serialized(path);
+    let statute = path.with_file_name(&file.statute);
+    assert!(
+        statute.exists(),
+        "Statute {} is not in {}.",
+        file.statute,
+        path.parent().unwrap().display()
+    );
+    let rules = get_normalization_rules(&file.normalization);
+
+    file.cases
+        .into_iter()
+        .filter_map(|case| {
+            let (normalized, _) = normalize_ballot(&rules, raw_ballot(&case.ballot));
+            let expected = NormalizedBallot::from_rankings("case".into(), case.choices, case.end);
+            if normalized == expected {
+                return None;
+            }
+            Some(format!(
+                "{} ({}{}): expected {:?}, got {:?}",
+                path.display(),
+                case.description,
+                case.section.map_or(String::new(), |s| format!(", {}", s)),
+                expected,
+                normalized
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn test_statute_cases() {
+    let statutes = Path::new(env!("CARGO_MANIFEST_DIR")).join("statutes");
+    let files: Vec<_> = get_files_from_path(&statutes)
+        .unwrap()
+        .into_iter()
+        .filter(|p| p.file_name().unwrap() == "normalization.json")
+        .collect();
+    assert!(!files.is_empty());
+
+    let failures: Vec<String> = files.iter().flat_map(|p| run_case_file(p)).collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
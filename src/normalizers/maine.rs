@@ -1,47 +1,21 @@
-use crate::model::election::{Ballot, Choice, NormalizedBallot};
-use std::collections::BTreeSet;
-
-pub fn maine_normalizer(ballot: Ballot) -> NormalizedBallot {
-    // "Exhausted ballot" means a ballot that does not rank any continuing candidate,
-    // contains an overvote at the highest continuing ranking or contains 2 or more
-    // sequential skipped rankings before its highest continuing ranking.
-    // [IB 2015, c. 3, §5 (NEW).]
-
-    let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
-    let mut new_choices = Vec::new();
-    let mut last_skipped = false;
-    let mut overvoted = false;
-
-    for choice in choices {
-        match choice {
-            Choice::Vote(v) => {
-                if !seen.contains(&v) {
-                    seen.insert(v);
-                    new_choices.push(v);
-                }
-                last_skipped = false;
-            }
-            Choice::Undervote => {
-                if last_skipped {
-                    break;
-                }
-                last_skipped = true;
-            }
-            Choice::Overvote => {
-                overvoted = true;
-                break;
-            }
-        }
-    }
-
-    NormalizedBallot::new(id, new_choices, overvoted)
-}
+use crate::model::metadata::{DuplicateRule, NormalizationRules, OvervoteRule};
+
+// "Exhausted ballot" means a ballot that does not rank any continuing candidate,
+// contains an overvote at the highest continuing ranking or contains 2 or more
+// sequential skipped rankings before its highest continuing ranking.
+// [IB 2015, c. 3, §5 (NEW).]
+pub const MAINE: NormalizationRules = NormalizationRules {
+    overvote: Some(OvervoteRule::Exhaust),
+    max_skipped_rankings: Some(1),
+    duplicates: Some(DuplicateRule::Ignore),
+    max_ranks: None,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::election::{CandidateId, Choice};
+    use crate::model::election::{Ballot, BallotEnd, CandidateId, Choice};
+    use crate::normalizers::rules::normalize_ballot;
 
     #[test]
     fn test_pass_through() {
@@ -50,12 +24,12 @@ mod tests {
         let c3 = Choice::Vote(CandidateId(3));
         let b = Ballot::new("1".into(), vec![c1, c2, c3]);
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(
             vec![CandidateId(1), CandidateId(2), CandidateId(3)],
             normalized.choices()
         );
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -65,9 +39,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, c2, c1]);
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -76,9 +50,9 @@ mod tests {
         let c1 = Choice::Vote(CandidateId(1));
         let b = Ballot::new("1".into(), vec![c1, c1, c1, c1]);
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(vec![CandidateId(1)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -88,9 +62,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, Choice::Undervote, c2]);
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -100,9 +74,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, Choice::Overvote, c2]);
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(vec![CandidateId(1)], normalized.choices());
-        assert_eq!(true, normalized.overvoted);
+        assert_eq!(BallotEnd::Overvote, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -115,9 +89,9 @@ mod tests {
             vec![c1, Choice::Undervote, Choice::Undervote, c2],
         );
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(vec![CandidateId(1)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::SkippedRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -131,12 +105,22 @@ mod tests {
             vec![c1, Choice::Undervote, c2, Choice::Undervote, c3],
         );
 
-        let normalized = maine_normalizer(b);
+        let normalized = normalize_ballot(&MAINE, b).0;
         assert_eq!(
             vec![CandidateId(1), CandidateId(2), CandidateId(3)],
             normalized.choices()
         );
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
+
+    #[test]
+    fn test_trailing_skipped_vote() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let b = Ballot::new("1".into(), vec![c1, Choice::Undervote, Choice::Undervote]);
+
+        let normalized = normalize_ballot(&MAINE, b).0;
+        assert_eq!(vec![CandidateId(1)], normalized.choices());
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
+    }
 }
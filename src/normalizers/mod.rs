@@ -1,24 +1,170 @@
+#[cfg(test)]
+mod conformance;
 mod maine;
+mod rules;
 mod simple;
 
-use crate::model::election::{Ballot, Election, NormalizedBallot, NormalizedElection};
+use crate::model::election::{CandidateId, Election, NormalizedElection};
+use crate::model::metadata::{Normalization, NormalizationRules};
+use crate::model::normalization::{BallotNormalization, NormalizationTrail};
+use crate::normalizers::rules::normalize_ballot;
+use std::collections::HashSet;
 
-type BallotNormalizer = dyn Fn(Ballot) -> NormalizedBallot;
-
-fn get_normalizer_for_format(format: &str) -> &'static BallotNormalizer {
-    match format {
-        "simple" => &simple::simple_normalizer,
-        "maine" => &maine::maine_normalizer,
-        _ => panic!("The normalizer {} is not implemented.", format),
+fn get_normalization_rules(normalization: &Normalization) -> NormalizationRules {
+    match normalization {
+        Normalization::Named(name) => match name.as_str() {
+            "simple" => simple::SIMPLE,
+            "maine" => maine::MAINE,
+            _ => panic!("The normalizer {} is not implemented.", name),
+        },
+        Normalization::Rules(rules) => *rules,
     }
 }
 
-pub fn normalize_election(format: &str, election: Election) -> NormalizedElection {
-    let normalizer = get_normalizer_for_format(format);
-    let ballots = election.ballots.into_iter().map(normalizer).collect();
+/// Normalize every ballot of an election. If `record_trail` is set, also
+/// record the rules that changed how each ballot was read.
+pub fn normalize_election(
+    normalization: &Normalization,
+    election: Election,
+    record_trail: bool,
+) -> (NormalizedElection, Option<NormalizationTrail>) {
+    let rules = get_normalization_rules(normalization);
+    let mut trail = record_trail.then(NormalizationTrail::default);
+    let ballots = election
+        .ballots
+        .into_iter()
+        .map(|ballot| {
+            let count = ballot.count;
+            let (mut normalized, events) = normalize_ballot(&rules, ballot);
+            normalized.count = count;
+
+            if let (Some(trail), false) = (&mut trail, events.is_empty()) {
+                for event in &events {
+                    *trail.counts.entry(event.action).or_default() += count;
+                }
+                trail.ballots.push(BallotNormalization {
+                    id: normalized.id.clone(),
+                    events,
+                });
+            }
+            normalized
+        })
+        .collect();
 
-    NormalizedElection {
+    let election = NormalizedElection {
         candidates: election.candidates,
         ballots,
+        withdrawn: Vec::new(),
+    };
+    (election, trail)
+}
+
+/// Remove withdrawn candidates, given by name or external ID, from every
+/// ballot so that rankings of them skip to the next ranking.
+pub fn withdraw_candidates(election: &mut NormalizedElection, withdrawn: &[String]) {
+    let ids: Vec<CandidateId> = withdrawn
+        .iter()
+        .map(|name| {
+            let index = election
+                .candidates
+                .iter()
+                .position(|c| &c.name == name || c.external_id.as_ref() == Some(name))
+                .unwrap_or_else(|| panic!("Withdrawn candidate {} is not in the contest.", name));
+            CandidateId(index as u32)
+        })
+        .collect();
+
+    let set: HashSet<CandidateId> = ids.iter().copied().collect();
+    for ballot in &mut election.ballots {
+        ballot.remove_candidates(&set);
+    }
+    election.withdrawn = ids;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{Ballot, BallotEnd, Candidate, CandidateType, Choice};
+    use crate::model::normalization::NormalizationAction;
+
+    #[test]
+    fn test_normalization_trail() {
+        let candidates = vec![Candidate::new("A".into(), CandidateType::Regular)];
+        let a = Choice::Vote(CandidateId(0));
+        let election = || {
+            Election::new(
+                candidates.clone(),
+                vec![
+                    Ballot::with_count("1".into(), vec![a, a], 3),
+                    Ballot::new("2".into(), vec![a]),
+                    Ballot::with_count("3".into(), vec![Choice::Overvote, a, a], 2),
+                ],
+            )
+        };
+
+        let simple = Normalization::Named("simple".into());
+        let (_, trail) = normalize_election(&simple, election(), false);
+        assert!(trail.is_none());
+
+        let (_, trail) = normalize_election(&simple, election(), true);
+        let trail = trail.unwrap();
+        let ids: Vec<&str> = trail.ballots.iter().map(|b| &b.id[..]).collect();
+        assert_eq!(vec!["1", "3"], ids);
+        assert_eq!(Some(&3), trail.counts.get(&NormalizationAction::Duplicate));
+        assert_eq!(
+            Some(&2),
+            trail.counts.get(&NormalizationAction::OvervoteExhausted)
+        );
+    }
+
+    #[test]
+    fn test_every_overvote_rule() {
+        // Every overvote rule that metadata accepts can normalize a ballot.
+        for (rule, end) in &[
+            ("exhaust", BallotEnd::Overvote),
+            ("skipRank", BallotEnd::NoMoreRankings),
+            ("exhaustIfMultipleContinuing", BallotEnd::NoMoreRankings),
+        ] {
+            let normalization: Normalization =
+                serde_json::from_str(&format!("{{\"overvote\": \"{}\"}}", rule)).unwrap();
+            let candidates = vec![
+                Candidate::new("A".into(), CandidateType::Regular),
+                Candidate::new("B".into(), CandidateType::Regular),
+            ];
+            let overvotes = vec![(0, vec![CandidateId(0), CandidateId(1)])];
+            let election = Election::new(
+                candidates,
+                vec![Ballot::new("1".into(), vec![Choice::Overvote])
+                    .with_overvotes(overvotes.into_iter().collect())],
+            );
+
+            let (normalized, _) = normalize_election(&normalization, election, false);
+            assert_eq!(*end, normalized.ballots[0].end, "{}", rule);
+        }
+    }
+
+    #[test]
+    fn test_withdraw_candidates() {
+        let mut candidates: Vec<Candidate> = ["A", "B", "C"]
+            .iter()
+            .map(|name| Candidate::new(name.to_string(), CandidateType::Regular))
+            .collect();
+        candidates[2].external_id = Some("17".to_string());
+        let votes = |ids: &[u32]| ids.iter().map(|i| Choice::Vote(CandidateId(*i))).collect();
+        let election = Election::new(
+            candidates,
+            vec![
+                Ballot::new("1".into(), votes(&[0, 1, 2])),
+                Ballot::new("2".into(), votes(&[2, 1])),
+            ],
+        );
+
+        let (mut normalized, _) =
+            normalize_election(&Normalization::Named("simple".into()), election, false);
+        withdraw_candidates(&mut normalized, &["A".to_string(), "17".to_string()]);
+
+        assert_eq!(vec![CandidateId(0), CandidateId(2)], normalized.withdrawn);
+        assert_eq!(vec![CandidateId(1)], normalized.ballots[0].choices());
+        assert_eq!(vec![CandidateId(1)], normalized.ballots[1].choices());
     }
 }
@@ -0,0 +1,272 @@
+use crate::model::election::{Ballot, BallotEnd, Choice, NormalizedBallot, Ranking};
+use crate::model::metadata::{DuplicateRule, NormalizationRules, OvervoteRule};
+use crate::model::normalization::{NormalizationAction, NormalizationEvent};
+use std::collections::BTreeSet;
+
+/// Read a ballot's rankings according to the given rules, and return the
+/// rules that changed how they were read.
+pub fn normalize_ballot(
+    rules: &NormalizationRules,
+    ballot: Ballot,
+) -> (NormalizedBallot, Vec<NormalizationEvent>) {
+    let overvote = rules.overvote.unwrap_or(OvervoteRule::Exhaust);
+    let duplicates = rules.duplicates.unwrap_or(DuplicateRule::Ignore);
+
+    let Ballot {
+        id,
+        choices,
+        mut overvotes,
+        source,
+        ..
+    } = ballot;
+    let max_ranks = rules.max_ranks.map_or(choices.len(), |m| m as usize);
+    let ignored = choices
+        .iter()
+        .skip(max_ranks)
+        .any(|c| *c != Choice::Undervote);
+    let mut choices = choices.into_iter().enumerate().take(max_ranks);
+    let mut seen = BTreeSet::new();
+    let mut rankings = Vec::new();
+    let mut skipped = 0;
+    let mut end = BallotEnd::NoMoreRankings;
+
+    let mut events = Vec::new();
+    let event = |rank: usize, action| NormalizationEvent {
+        rank: rank as u32 + 1,
+        action,
+    };
+    // Skipped rankings are only worth recording once a later ranking is
+    // read, rather than at the end of the ballot.
+    let mut pending: Vec<usize> = Vec::new();
+
+    for (rank, choice) in choices.by_ref() {
+        if choice != Choice::Undervote {
+            events.extend(
+                pending
+                    .drain(..)
+                    .map(|r| event(r, NormalizationAction::SkippedRanking)),
+            );
+        }
+
+        let skip = match choice {
+            Choice::Vote(v) if !seen.contains(&v) => {
+                seen.insert(v);
+                rankings.push(Ranking::Candidate(v));
+                false
+            }
+            Choice::Vote(_) => {
+                events.push(event(rank, NormalizationAction::Duplicate));
+                duplicates == DuplicateRule::Skip
+            }
+            Choice::Undervote => {
+                pending.push(rank);
+                true
+            }
+            Choice::Overvote => match (overvote, overvotes.remove(&rank)) {
+                (OvervoteRule::SkipRank, _) => {
+                    events.push(event(rank, NormalizationAction::OvervoteSkipped));
+                    continue;
+                }
+                // Which candidates are continuing is only known during
+                // tabulation, so the overvote is kept for it to resolve.
+                (OvervoteRule::ExhaustIfMultipleContinuing, Some(candidates)) => {
+                    events.push(event(rank, NormalizationAction::OvervoteKept));
+                    rankings.push(Ranking::Overvote(candidates));
+                    false
+                }
+                // If the ballot data doesn't record which candidates were
+                // marked, the overvote exhausts the ballot.
+                _ => {
+                    events.push(event(rank, NormalizationAction::OvervoteExhausted));
+                    end = BallotEnd::Overvote;
+                    break;
+                }
+            },
+        };
+
+        if !skip {
+            skipped = 0;
+            continue;
+        }
+        skipped += 1;
+        if rules.max_skipped_rankings.is_some_and(|max| skipped > max) {
+            // The skipped rankings only exhaust the ballot if the voter
+            // ranked a candidate after them. Otherwise the voter just
+            // stopped ranking, even if a later ranking is overvoted.
+            if choices.any(|(_, c)| matches!(c, Choice::Vote(_))) {
+                events.push(event(rank, NormalizationAction::SkippedRankingsExhausted));
+                end = BallotEnd::SkippedRankings;
+            }
+            break;
+        }
+    }
+
+    if ignored && end == BallotEnd::NoMoreRankings {
+        events.push(event(max_ranks, NormalizationAction::RanksIgnored));
+    }
+
+    let mut normalized = NormalizedBallot::from_rankings(id, rankings, end);
+    normalized.source = source;
+    (normalized, events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateId;
+
+    fn normalize(rules: NormalizationRules, choices: Vec<Choice>) -> NormalizedBallot {
+        normalize_ballot(&rules, Ballot::new("1".into(), choices)).0
+    }
+
+    #[test]
+    fn test_skip_overvoted_rank() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let c2 = Choice::Vote(CandidateId(2));
+        let rules = NormalizationRules {
+            overvote: Some(OvervoteRule::SkipRank),
+            max_skipped_rankings: Some(0),
+            ..Default::default()
+        };
+
+        // The overvote isn't a skipped ranking.
+        let normalized = normalize(rules, vec![c1, Choice::Overvote, c2]);
+        assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
+    }
+
+    #[test]
+    fn test_keep_overvoted_candidates() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let rules = NormalizationRules {
+            overvote: Some(OvervoteRule::ExhaustIfMultipleContinuing),
+            ..Default::default()
+        };
+        let overvotes = vec![(1, vec![CandidateId(2), CandidateId(3)])];
+        let ballot = Ballot::new("1".into(), vec![c1, Choice::Overvote, c1, Choice::Overvote])
+            .with_overvotes(overvotes.into_iter().collect());
+
+        // The second overvote's candidates aren't known, so it exhausts the
+        // ballot.
+        let (normalized, _) = normalize_ballot(&rules, ballot);
+        assert_eq!(
+            NormalizedBallot::from_rankings(
+                "1".into(),
+                vec![
+                    Ranking::Candidate(CandidateId(1)),
+                    Ranking::Overvote(vec![CandidateId(2), CandidateId(3)]),
+                ],
+                BallotEnd::Overvote,
+            ),
+            normalized
+        );
+    }
+
+    #[test]
+    fn test_skipped_rankings_before_overvote() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let rules = NormalizationRules {
+            max_skipped_rankings: Some(1),
+            ..Default::default()
+        };
+
+        let normalized = normalize(
+            rules,
+            vec![c1, Choice::Undervote, Choice::Undervote, Choice::Overvote],
+        );
+        assert_eq!(vec![CandidateId(1)], normalized.choices());
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
+    }
+
+    #[test]
+    fn test_duplicate_as_skipped() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let c2 = Choice::Vote(CandidateId(2));
+        let rules = NormalizationRules {
+            max_skipped_rankings: Some(1),
+            duplicates: Some(DuplicateRule::Skip),
+            ..Default::default()
+        };
+
+        let normalized = normalize(rules, vec![c1, c1, Choice::Undervote, c2]);
+        assert_eq!(vec![CandidateId(1)], normalized.choices());
+        assert_eq!(BallotEnd::SkippedRankings, normalized.end);
+
+        let rules = NormalizationRules {
+            duplicates: Some(DuplicateRule::Ignore),
+            ..rules
+        };
+        let normalized = normalize(rules, vec![c1, c1, Choice::Undervote, c2]);
+        assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
+    }
+
+    #[test]
+    fn test_no_skipped_rankings() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let c2 = Choice::Vote(CandidateId(2));
+        let rules = NormalizationRules {
+            max_skipped_rankings: Some(0),
+            ..Default::default()
+        };
+
+        let normalized = normalize(rules, vec![c1, Choice::Undervote, c2]);
+        assert_eq!(vec![CandidateId(1)], normalized.choices());
+        assert_eq!(BallotEnd::SkippedRankings, normalized.end);
+    }
+
+    #[test]
+    fn test_events() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let c2 = Choice::Vote(CandidateId(2));
+        let ballot = Ballot::new(
+            "1".into(),
+            vec![c1, Choice::Undervote, c2, c1, Choice::Overvote, c2],
+        );
+        let event = |rank, action| NormalizationEvent { rank, action };
+
+        let (_, events) = normalize_ballot(&NormalizationRules::default(), ballot);
+        assert_eq!(
+            vec![
+                event(2, NormalizationAction::SkippedRanking),
+                event(4, NormalizationAction::Duplicate),
+                event(5, NormalizationAction::OvervoteExhausted),
+            ],
+            events
+        );
+
+        // Skipped rankings at the end of a ballot aren't worth recording.
+        let ballot = Ballot::new("2".into(), vec![c1, Choice::Undervote, Choice::Undervote]);
+        let rules = NormalizationRules {
+            max_skipped_rankings: Some(1),
+            ..Default::default()
+        };
+        assert!(normalize_ballot(&rules, ballot).1.is_empty());
+
+        let ballot = Ballot::new("3".into(), vec![c1, Choice::Undervote, c2]);
+        let rules = NormalizationRules {
+            max_ranks: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![event(3, NormalizationAction::RanksIgnored)],
+            normalize_ballot(&rules, ballot).1
+        );
+    }
+
+    #[test]
+    fn test_max_ranks() {
+        let c1 = Choice::Vote(CandidateId(1));
+        let c2 = Choice::Vote(CandidateId(2));
+        let c3 = Choice::Vote(CandidateId(3));
+        let rules = NormalizationRules {
+            max_ranks: Some(2),
+            ..Default::default()
+        };
+
+        let normalized = normalize(rules, vec![c1, c2, c3]);
+        assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
+
+        let normalized = normalize(rules, vec![c1, c2, Choice::Overvote]);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
+    }
+}
@@ -1,40 +1,22 @@
-use crate::model::election::{Ballot, Choice, NormalizedBallot};
-use std::collections::BTreeSet;
+use crate::model::metadata::{DuplicateRule, NormalizationRules, OvervoteRule};
 
-pub fn simple_normalizer(ballot: Ballot) -> NormalizedBallot {
-    // In most jurisdiction, ranks are considered as follows:
-    // For each ballot, consider the top-ranked candidate who
-    // has not yet been eliminated. If the top-ranked candidate
-    // is ambiguous (i.e. an overvote), consider the ballot
-    // exhausted.
-    let mut seen = BTreeSet::new();
-    let Ballot { id, choices } = ballot;
-    let mut new_choices = Vec::new();
-    let mut overvoted = false;
-
-    for choice in choices {
-        match choice {
-            Choice::Vote(v) => {
-                if !seen.contains(&v) {
-                    seen.insert(v);
-                    new_choices.push(v);
-                }
-            }
-            Choice::Overvote => {
-                overvoted = true;
-                break;
-            }
-            _ => (),
-        }
-    }
-
-    NormalizedBallot::new(id, new_choices, overvoted)
-}
+// In most jurisdiction, ranks are considered as follows:
+// For each ballot, consider the top-ranked candidate who
+// has not yet been eliminated. If the top-ranked candidate
+// is ambiguous (i.e. an overvote), consider the ballot
+// exhausted.
+pub const SIMPLE: NormalizationRules = NormalizationRules {
+    overvote: Some(OvervoteRule::Exhaust),
+    max_skipped_rankings: None,
+    duplicates: Some(DuplicateRule::Ignore),
+    max_ranks: None,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::election::{CandidateId, Choice};
+    use crate::model::election::{Ballot, BallotEnd, CandidateId, Choice};
+    use crate::normalizers::rules::normalize_ballot;
 
     #[test]
     fn test_pass_through() {
@@ -43,12 +25,12 @@ mod tests {
         let c3 = Choice::Vote(CandidateId(3));
         let b = Ballot::new("1".into(), vec![c1, c2, c3]);
 
-        let normalized = simple_normalizer(b);
+        let normalized = normalize_ballot(&SIMPLE, b).0;
         assert_eq!(
             vec![CandidateId(1), CandidateId(2), CandidateId(3)],
             normalized.choices()
         );
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -58,9 +40,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, c2, c1]);
 
-        let normalized = simple_normalizer(b);
+        let normalized = normalize_ballot(&SIMPLE, b).0;
         assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -69,9 +51,9 @@ mod tests {
         let c1 = Choice::Vote(CandidateId(1));
         let b = Ballot::new("1".into(), vec![c1, c1, c1, c1]);
 
-        let normalized = simple_normalizer(b);
+        let normalized = normalize_ballot(&SIMPLE, b).0;
         assert_eq!(vec![CandidateId(1)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -81,9 +63,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, Choice::Undervote, c2]);
 
-        let normalized = simple_normalizer(b);
+        let normalized = normalize_ballot(&SIMPLE, b).0;
         assert_eq!(vec![CandidateId(1), CandidateId(2)], normalized.choices());
-        assert_eq!(false, normalized.overvoted);
+        assert_eq!(BallotEnd::NoMoreRankings, normalized.end);
         assert_eq!("1", normalized.id);
     }
 
@@ -93,9 +75,9 @@ mod tests {
         let c2 = Choice::Vote(CandidateId(2));
         let b = Ballot::new("1".into(), vec![c1, Choice::Overvote, c2]);
 
-        let normalized = simple_normalizer(b);
+        let normalized = normalize_ballot(&SIMPLE, b).0;
         assert_eq!(vec![CandidateId(1)], normalized.choices());
-        assert_eq!(true, normalized.overvoted);
+        assert_eq!(BallotEnd::Overvote, normalized.end);
         assert_eq!("1", normalized.id);
     }
 }
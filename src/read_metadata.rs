@@ -1,16 +1,24 @@
 use crate::model::metadata::Jurisdiction;
+use crate::tabulator::get_tabulation_method;
 use crate::util::{get_files_from_path, read_serialized};
 use colored::*;
 use std::path::{Path, PathBuf};
 
 /// Read all metadata files under the given directory (recursively) and return
-/// an iterator over the results.
+/// an iterator over the results. Panics if a file names a tabulation method
+/// that is not implemented, before any of its ballots are read.
 pub fn read_meta(path: &Path) -> impl Iterator<Item = (PathBuf, Jurisdiction)> {
     let files = get_files_from_path(path).unwrap();
 
     files.into_iter().map(|file| {
         eprintln!("File: {}", file.to_string_lossy().blue());
-        let ec = read_serialized(&file);
+        let ec: Jurisdiction = read_serialized(&file);
+        for election in ec.elections.values() {
+            let options = election.tabulation_options.as_ref();
+            if let Some(method) = options.and_then(|o| o.method.as_deref()) {
+                get_tabulation_method(method);
+            }
+        }
         (file, ec)
     })
 }
@@ -1,25 +1,29 @@
+use crate::alternative::alternative_methods;
+use crate::condorcet::{
+    minimax, pairwise_counts, pairwise_graph, ranked_pairs, schulze, schwartz_set, smith_set,
+};
 use crate::formats::read_election;
+use crate::margin::irv_margin;
 use crate::model::election::{
     CandidateId, CandidateType, ElectionInfo, ElectionPreprocessed, NormalizedBallot,
 };
 use crate::model::metadata::{Contest, ElectionMetadata, Jurisdiction};
-use crate::model::report::{CandidatePairEntry, CandidatePairTable, CandidateVotes, ContestReport};
-use crate::normalizers::normalize_election;
-use crate::tabulator::{tabulate, Allocatee, TabulatorRound};
+use crate::model::normalization::NormalizationTrail;
+use crate::model::report::{
+    CandidatePairEntry, CandidatePairTable, CandidateVotes, CondorcetMethods, ContestReport,
+};
+use crate::normalizers::{normalize_election, withdraw_candidates};
+use crate::tabulator::{get_tabulation_method, Allocatee, TabulatorRound};
 use colored::*;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 
-pub fn winner(rounds: &[TabulatorRound]) -> CandidateId {
+/// Return the elected candidates, in the order they were elected.
+pub fn winners(rounds: &[TabulatorRound]) -> Vec<CandidateId> {
     rounds
-        .last()
-        .unwrap()
-        .allocations
-        .first()
-        .unwrap()
-        .allocatee
-        .candidate_id()
-        .unwrap()
+        .iter()
+        .flat_map(|round| round.elected.iter().copied())
+        .collect()
 }
 
 pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
@@ -32,20 +36,18 @@ pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
         })
         .collect();
 
-    let mut candidate_to_final_votes: BTreeMap<CandidateId, u32> =
-        candidate_to_initial_votes.clone();
-
+    let mut candidate_to_transfer_votes: BTreeMap<CandidateId, u32> = BTreeMap::new();
     let mut round_eliminated: BTreeMap<CandidateId, u32> = BTreeMap::new();
 
-    for (i, round) in rounds[1..].iter().enumerate() {
-        for alloc in &round.allocations {
-            if let Allocatee::Candidate(c) = alloc.allocatee {
-                candidate_to_final_votes.insert(c, alloc.votes);
+    for (i, round) in rounds.iter().enumerate() {
+        for transfer in &round.transfers {
+            if let Allocatee::Candidate(c) = transfer.to {
+                *candidate_to_transfer_votes.entry(c).or_default() += transfer.count;
             }
         }
 
-        for transfer in &round.transfers {
-            round_eliminated.insert(transfer.from, (i + 1) as u32);
+        for candidate in &round.eliminated {
+            round_eliminated.insert(*candidate, (i + 1) as u32);
         }
     }
 
@@ -54,7 +56,10 @@ pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
         .map(|(candidate, first_round_votes)| CandidateVotes {
             candidate,
             first_round_votes,
-            transfer_votes: candidate_to_final_votes[&candidate] - first_round_votes,
+            transfer_votes: candidate_to_transfer_votes
+                .get(&candidate)
+                .cloned()
+                .unwrap_or_default(),
             round_eliminated: round_eliminated.get(&candidate).cloned(),
         })
         .collect();
@@ -64,36 +69,6 @@ pub fn total_votes(rounds: &[TabulatorRound]) -> Vec<CandidateVotes> {
     result
 }
 
-pub fn generate_pairwise_counts(
-    candidates: &[CandidateId],
-    ballots: &[NormalizedBallot],
-) -> HashMap<(CandidateId, CandidateId), u32> {
-    let mut preference_map: HashMap<(CandidateId, CandidateId), u32> = HashMap::new();
-    let all_candidates: HashSet<CandidateId> = candidates.iter().copied().collect();
-
-    for ballot in ballots {
-        let mut above_ranked: HashSet<CandidateId> = HashSet::new();
-
-        for vote in ballot.choices() {
-            for arc in &above_ranked {
-                *preference_map.entry((*arc, vote)).or_insert(0) += 1;
-            }
-
-            above_ranked.insert(vote);
-        }
-
-        let remaining = all_candidates.difference(&above_ranked);
-
-        for candidate in remaining {
-            for arc in &above_ranked {
-                *preference_map.entry((*arc, *candidate)).or_insert(0) += 1;
-            }
-        }
-    }
-
-    preference_map
-}
-
 pub fn generate_pairwise_preferences(
     candidates: &[CandidateId],
     preference_map: &HashMap<(CandidateId, CandidateId), u32>,
@@ -138,14 +113,15 @@ pub fn generate_first_alternate(
     let mut alternate_map: HashMap<(CandidateId, Allocatee), u32> = HashMap::new();
 
     for ballot in ballots {
-        let choices = ballot.choices();
-        if let Some(first) = choices.first() {
-            let second = choices
-                .get(1)
+        if let Some(first) = ballot.choices().first() {
+            // Where the ballot would go if its first choice were eliminated.
+            let second = ballot
+                .ranking_among(|c| c != *first)
+                .first()
                 .map(|d| Allocatee::Candidate(*d))
                 .unwrap_or(Allocatee::Exhausted);
-            *alternate_map.entry((*first, second)).or_insert(0) += 1;
-            *first_choice_count.entry(*first).or_insert(0) += 1;
+            *alternate_map.entry((*first, second)).or_insert(0) += ballot.count;
+            *first_choice_count.entry(*first).or_insert(0) += ballot.count;
         }
     }
 
@@ -190,17 +166,18 @@ pub fn generate_first_final(
     let mut first_total: HashMap<CandidateId, u32> = HashMap::new();
 
     for ballot in ballots {
-        let choices = ballot.choices();
-        if let Some(first) = choices.first() {
+        if let Some(first) = ballot.choices().first() {
             if !final_round_candidates.contains(first) {
-                let final_choice = match choices.iter().find(|x| final_round_candidates.contains(x))
+                let final_choice = match ballot
+                    .ranking_among(|c| final_round_candidates.contains(&c))
+                    .first()
                 {
                     Some(v) => Allocatee::Candidate(*v),
                     _ => Allocatee::Exhausted,
                 };
 
-                *first_final.entry((*first, final_choice)).or_insert(0) += 1;
-                *first_total.entry(*first).or_insert(0) += 1;
+                *first_final.entry((*first, final_choice)).or_insert(0) += ballot.count;
+                *first_total.entry(*first).or_insert(0) += ballot.count;
             }
         }
     }
@@ -245,79 +222,78 @@ pub fn generate_first_final(
     }
 }
 
-pub fn graph(
-    candidates: &[CandidateId],
-    preference_map: &HashMap<(CandidateId, CandidateId), u32>,
-) -> HashMap<CandidateId, Vec<CandidateId>> {
-    let mut graph = HashMap::new();
-
-    for c1 in candidates {
-        for c2 in candidates {
-            let c1v = preference_map.get(&(*c1, *c2)).unwrap_or(&0);
-            let c2v = preference_map.get(&(*c2, *c1)).unwrap_or(&0);
-
-            if c1v > c2v {
-                graph.entry(*c2).or_insert_with(Vec::new).push(*c1);
-            }
-        }
+/// Generate a `ContestReport` from preprocessed election data and its
+/// grouped ballots.
+pub fn generate_report(
+    election: &ElectionPreprocessed,
+    ballots: &[NormalizedBallot],
+) -> ContestReport {
+    let options = &election.info.tabulation_options;
+    let seats = options.seats();
+    let method = get_tabulation_method(options.tabulation_method());
+    let mut seat_rounds = method.tabulate(
+        &election.ballots.candidates,
+        &election.ballots.ballots,
+        ballots,
+        options,
+    );
+    let rounds = match seat_rounds.first() {
+        Some(rounds) => rounds.clone(),
+        None => panic!("Tabulating {} produced no count.", election.info.office),
+    };
+    if seat_rounds.len() == 1 {
+        seat_rounds.clear();
     }
-
-    graph
-}
-
-pub fn smith_set(
-    candidates: &[CandidateId],
-    graph: &HashMap<CandidateId, Vec<CandidateId>>,
-) -> HashSet<CandidateId> {
-    let mut last_set: HashSet<CandidateId> = candidates.iter().cloned().collect();
-
-    loop {
-        let this_set: HashSet<CandidateId> = last_set
+    let winners: Vec<CandidateId> = if seat_rounds.is_empty() {
+        winners(&rounds)
+    } else {
+        seat_rounds
             .iter()
-            .flat_map(|d| graph.get(d).cloned().unwrap_or_default())
-            .collect();
-
-        if this_set.is_empty() || this_set == last_set {
-            break;
-        }
-
-        last_set = this_set;
-    }
-
-    last_set
-}
-
-/// Generate a `ContestReport` from preprocessed election data.
-pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
-    let ballots = &election.ballots.ballots;
-    let rounds = tabulate(ballots);
-    let winner = winner(&rounds);
+            .flat_map(|rounds| winners(rounds))
+            .collect()
+    };
+    let winner = match winners.first() {
+        Some(winner) => *winner,
+        None => panic!("Tabulating {} elected no one.", election.info.office),
+    };
+    let withdrawn = &election.ballots.withdrawn;
     let num_candidates = election
         .ballots
         .candidates
         .iter()
-        .filter(|d| d.candidate_type != CandidateType::WriteIn)
+        .enumerate()
+        .filter(|(i, d)| {
+            d.candidate_type != CandidateType::WriteIn
+                && !withdrawn.contains(&CandidateId(*i as u32))
+        })
         .count() as u32;
 
+    // For methods that count once per seat, this summarizes the count for
+    // the first seat only.
     let total_votes = total_votes(&rounds);
     let candidates: Vec<CandidateId> = total_votes.iter().map(|d| d.candidate).collect();
 
-    let pairwise_counts: HashMap<(CandidateId, CandidateId), u32> =
-        generate_pairwise_counts(&candidates, ballots);
+    let pairwise_counts = pairwise_counts(&candidates, ballots);
 
     let pairwise_preferences = generate_pairwise_preferences(&candidates, &pairwise_counts);
-    let graph = graph(&candidates, &pairwise_counts);
-    let smith_set = smith_set(&candidates, &graph);
-    let condorcet = if smith_set.len() == 1 {
-        smith_set.iter().next().copied()
-    } else {
-        None
+    let pairwise_graph = pairwise_graph(&candidates, &pairwise_counts);
+    let smith_set = smith_set(&candidates, &pairwise_counts);
+    let schwartz_set = schwartz_set(&candidates, &pairwise_counts);
+    let condorcet = match smith_set.as_slice() {
+        [winner] => Some(*winner),
+        _ => None,
     };
 
-    if Some(winner) != condorcet {
+    if seats == 1 && Some(winner) != condorcet {
         eprintln!("{}", "Non-condorcet!".purple());
     }
 
+    let condorcet_methods = CondorcetMethods {
+        ranked_pairs: ranked_pairs(&candidates, &pairwise_counts),
+        schulze: schulze(&candidates, &pairwise_counts),
+        minimax: minimax(&candidates, &pairwise_counts),
+    };
+
     let first_alternate = generate_first_alternate(&candidates, ballots);
 
     let final_round_candidates: HashSet<CandidateId> = rounds
@@ -330,47 +306,80 @@ pub fn generate_report(election: &ElectionPreprocessed) -> ContestReport {
 
     let first_final = generate_first_final(&candidates, ballots, &final_round_candidates);
 
+    let margin = if options.is_standard_irv() {
+        irv_margin(&candidates, ballots, winner)
+    } else {
+        None
+    };
+
     ContestReport {
         info: election.info.clone(),
-        ballot_count: election.ballots.ballots.len() as u32,
+        ballot_count: ballots.iter().map(|b| b.count).sum(),
         candidates: election.ballots.candidates.clone(),
         winner,
+        winners,
+        withdrawn: withdrawn.clone(),
         num_candidates,
         rounds,
+        seat_rounds,
         total_votes,
         pairwise_preferences,
         first_alternate,
         first_final,
-        smith_set: smith_set.into_iter().collect(),
+        smith_set,
+        schwartz_set,
+        pairwise_graph: Some(pairwise_graph),
         condorcet,
+        condorcet_methods: Some(condorcet_methods),
+        alternative_methods: Some(alternative_methods(&candidates, ballots, options)),
+        margin,
     }
 }
 
 /// Preprocess an election by reading and normalizing the raw ballot data according
-/// to the rules given in the metadata for this contest.
+/// to the rules given in the metadata for this contest. If `normalization_trail`
+/// is set, also returns the record of which rules changed how each ballot was
+/// read.
 pub fn preprocess_election(
     raw_base: &Path,
     metadata: &ElectionMetadata,
     election_path: &str,
     ec: &Jurisdiction,
     contest: &Contest,
-) -> ElectionPreprocessed {
+    normalization_trail: bool,
+) -> (ElectionPreprocessed, Option<NormalizationTrail>) {
     let election = read_election(
         &metadata.data_format,
-        &raw_base.join(&election_path),
+        &raw_base.join(election_path),
         contest.loader_params.clone().unwrap_or_default(),
+        metadata.write_ins,
     );
     let office = ec.offices.get(&contest.office).unwrap();
 
-    let normalized_election = normalize_election(&metadata.normalization, election);
+    let mut tabulation_options = metadata.tabulation_options.clone().unwrap_or_default();
+    tabulation_options.seats = contest
+        .seats
+        .or(tabulation_options.seats)
+        .or(election.seats);
+    if tabulation_options.seats == Some(0) {
+        panic!("Contest {} should have at least one seat.", contest.office);
+    }
+    // Check the method fills this many seats before normalizing the ballots.
+    tabulation_options.tabulation_method();
+
+    let (mut normalized_election, trail) =
+        normalize_election(&metadata.normalization, election, normalization_trail);
+    if let Some(withdrawn) = &contest.withdrawn {
+        withdraw_candidates(&mut normalized_election, withdrawn);
+    }
 
-    ElectionPreprocessed {
+    let preprocessed = ElectionPreprocessed {
         info: ElectionInfo {
             name: office.name.clone(),
             office: contest.office.clone(),
             date: metadata.date.clone(),
             data_format: metadata.data_format.clone(),
-            tabulation_options: metadata.tabulation_options.clone().unwrap_or_default(),
+            tabulation_options,
             loader_params: contest.loader_params.clone(),
             jurisdiction_path: ec.path.clone(),
             election_path: election_path.to_string(),
@@ -380,5 +389,6 @@ pub fn preprocess_election(
             website: metadata.website.clone(),
         },
         ballots: normalized_election,
-    }
+    };
+    (preprocessed, trail)
 }
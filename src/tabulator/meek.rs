@@ -0,0 +1,513 @@
+use crate::model::election::{BallotEnd, Candidate, CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::tabulator::schema::KeepFactor;
+use crate::tabulator::tie_break::TieBreaker;
+use crate::tabulator::{
+    Allocatee, ExhaustedBallots, TabulatorAllocation, TabulatorRound, Transfer,
+};
+use crate::util::FixedPoint;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+/// Stop adjusting keep factors once elected candidates' combined surplus is
+/// at most this many votes (0.00001).
+const TOLERANCE: FixedPoint = FixedPoint::from_raw(10_000);
+
+/// Give up on adjusting keep factors after this many iterations.
+const MAX_ITERATIONS: usize = 1000;
+
+/// How a ballot's value is shared among the candidates it ranks.
+#[derive(Clone, Copy, PartialEq)]
+enum Rule {
+    /// Each candidate keeps their keep factor's share of the value that
+    /// reaches them.
+    Meek,
+    /// Each candidate keeps their keep factor's share of the ballot's full
+    /// value, or whatever value remains if that is less.
+    Warren,
+}
+
+/// Whether a candidate is still running, which is whether they keep any of
+/// the value that reaches them.
+fn continuing(keep: &HashMap<CandidateId, FixedPoint>) -> impl Fn(CandidateId) -> bool + '_ {
+    move |c| keep.get(&c).is_some_and(|k| *k > FixedPoint::ZERO)
+}
+
+/// Return the value a ballot gives to each candidate it ranks, and the value
+/// left over once its rankings run out.
+fn distribute(
+    ballot: &NormalizedBallot,
+    keep: &HashMap<CandidateId, FixedPoint>,
+    rule: Rule,
+) -> (HashMap<CandidateId, FixedPoint>, FixedPoint) {
+    let value = FixedPoint::from_int(ballot.count);
+    let mut remaining = value;
+    let mut shares = HashMap::new();
+
+    for candidate in ballot.ranking_among(continuing(keep)) {
+        if remaining == FixedPoint::ZERO {
+            break;
+        }
+
+        let keep = keep.get(&candidate).copied().unwrap_or(FixedPoint::ZERO);
+        let share = match rule {
+            Rule::Meek => remaining * keep,
+            Rule::Warren => (value * keep).min(remaining),
+        };
+        remaining -= share;
+        shares.insert(candidate, share);
+    }
+
+    (shares, remaining)
+}
+
+/// Votes for each candidate and exhausted value, from one pass over the
+/// ballots.
+struct Count {
+    votes: HashMap<CandidateId, FixedPoint>,
+    blank: FixedPoint,
+    /// Exhausted value by why the ballot's rankings ended.
+    exhausted: HashMap<BallotEnd, FixedPoint>,
+}
+
+impl Count {
+    fn new(
+        ballots: &[NormalizedBallot],
+        keep: &HashMap<CandidateId, FixedPoint>,
+        rule: Rule,
+    ) -> Count {
+        let mut votes: HashMap<CandidateId, FixedPoint> = HashMap::new();
+        let mut blank = FixedPoint::ZERO;
+        let mut exhausted: HashMap<BallotEnd, FixedPoint> = HashMap::new();
+
+        for ballot in ballots {
+            let (shares, remaining) = distribute(ballot, keep, rule);
+            for (candidate, share) in shares {
+                *votes.entry(candidate).or_default() += share;
+            }
+
+            if ballot.rankings().next().is_none() && ballot.end == BallotEnd::NoMoreRankings {
+                blank += remaining;
+            } else {
+                *exhausted
+                    .entry(ballot.end_among(continuing(keep)))
+                    .or_default() += remaining;
+            }
+        }
+
+        Count {
+            votes,
+            blank,
+            exhausted,
+        }
+    }
+
+    fn votes(&self, candidate: CandidateId) -> FixedPoint {
+        self.votes.get(&candidate).copied().unwrap_or_default()
+    }
+
+    fn exhausted(&self, end: BallotEnd) -> FixedPoint {
+        self.exhausted.get(&end).copied().unwrap_or_default()
+    }
+
+    /// The Droop quota of the votes held by candidates, plus the smallest
+    /// representable amount.
+    fn quota(&self, seats: u32) -> FixedPoint {
+        let continuing: FixedPoint = self.votes.values().copied().sum();
+        continuing / FixedPoint::from_int(seats + 1) + FixedPoint::EPSILON
+    }
+}
+
+/// Lower the keep factors of the elected candidates until each of them has
+/// no more than the quota, and return the resulting count.
+fn converge(
+    ballots: &[NormalizedBallot],
+    keep: &mut HashMap<CandidateId, FixedPoint>,
+    elected: &[CandidateId],
+    seats: u32,
+    rule: Rule,
+) -> Count {
+    for _ in 0..MAX_ITERATIONS {
+        let count = Count::new(ballots, keep, rule);
+        let quota = count.quota(seats);
+        let surplus: FixedPoint = elected
+            .iter()
+            .map(|c| count.votes(*c).saturating_sub(quota))
+            .sum();
+
+        if surplus <= TOLERANCE {
+            return count;
+        }
+
+        for candidate in elected {
+            let votes = count.votes(*candidate);
+            if votes > FixedPoint::ZERO {
+                let factor = keep[candidate].mul_div_ceil(quota, votes);
+                keep.insert(*candidate, factor.min(FixedPoint::ONE));
+            }
+        }
+    }
+
+    Count::new(ballots, keep, rule)
+}
+
+/// Return the value that moved between candidates when keep factors changed
+/// from `before` to `after`. Value a candidate loses on a ballot is credited
+/// to the candidates ranked below them who gain it, in ranking order.
+fn transfers(
+    ballots: &[NormalizedBallot],
+    before: &HashMap<CandidateId, FixedPoint>,
+    after: &HashMap<CandidateId, FixedPoint>,
+    rule: Rule,
+) -> Vec<Transfer> {
+    let mut flows: BTreeMap<(CandidateId, Allocatee), FixedPoint> = BTreeMap::new();
+
+    for ballot in ballots {
+        let (old, old_remaining) = distribute(ballot, before, rule);
+        let (new, new_remaining) = distribute(ballot, after, rule);
+        let mut released: VecDeque<(CandidateId, FixedPoint)> = VecDeque::new();
+
+        for candidate in ballot.ranked_candidates() {
+            let old_share = old.get(&candidate).copied().unwrap_or_default();
+            let new_share = new.get(&candidate).copied().unwrap_or_default();
+
+            if new_share < old_share {
+                released.push_back((candidate, old_share - new_share));
+            } else if new_share > old_share {
+                receive(
+                    &mut flows,
+                    &mut released,
+                    Allocatee::Candidate(candidate),
+                    new_share - old_share,
+                );
+            }
+        }
+
+        if new_remaining > old_remaining {
+            receive(
+                &mut flows,
+                &mut released,
+                Allocatee::Exhausted,
+                new_remaining - old_remaining,
+            );
+        }
+    }
+
+    let mut transfers: Vec<Transfer> = flows
+        .into_iter()
+        .map(|((from, to), value)| Transfer {
+            from,
+            to,
+            count: value.floor(),
+            exact_count: Some(value),
+        })
+        .collect();
+    transfers.sort_by_key(|t| Reverse(t.exact_count));
+    transfers
+}
+
+/// Credit `amount` gained by `to` to the candidates who released it, oldest
+/// first.
+fn receive(
+    flows: &mut BTreeMap<(CandidateId, Allocatee), FixedPoint>,
+    released: &mut VecDeque<(CandidateId, FixedPoint)>,
+    to: Allocatee,
+    mut amount: FixedPoint,
+) {
+    while amount > FixedPoint::ZERO {
+        let (from, available) = match released.front_mut() {
+            Some(front) => front,
+            None => break,
+        };
+        let moved = amount.min(*available);
+        *flows.entry((*from, to)).or_default() += moved;
+        *available -= moved;
+        amount -= moved;
+        if *available == FixedPoint::ZERO {
+            released.pop_front();
+        }
+    }
+}
+
+fn allocation(allocatee: Allocatee, votes: FixedPoint) -> TabulatorAllocation {
+    TabulatorAllocation {
+        allocatee,
+        votes: votes.floor(),
+        exact_votes: Some(votes),
+    }
+}
+
+/// Build the round for a count. `standing` lists the candidates who are
+/// elected or hopeful, and `elected` those elected before this round.
+#[allow(clippy::too_many_arguments)]
+fn as_round(
+    count: &Count,
+    quota: FixedPoint,
+    standing: &[(CandidateId, FixedPoint)],
+    elected: &[CandidateId],
+    keep: &HashMap<CandidateId, FixedPoint>,
+    transfers: Vec<Transfer>,
+    newly_elected: Vec<CandidateId>,
+    eliminated: Vec<CandidateId>,
+) -> TabulatorRound {
+    let undervote = count.blank
+        + count.exhausted(BallotEnd::NoMoreRankings)
+        + count.exhausted(BallotEnd::SkippedRankings);
+    let overvote = count.exhausted(BallotEnd::Overvote);
+    let continuing: FixedPoint = standing.iter().map(|(_, votes)| *votes).sum();
+
+    let mut allocations: Vec<TabulatorAllocation> = standing
+        .iter()
+        .map(|(c, votes)| allocation(Allocatee::Candidate(*c), *votes))
+        .collect();
+    allocations.push(allocation(Allocatee::Exhausted, undervote + overvote));
+
+    TabulatorRound {
+        allocations,
+        undervote: undervote.floor(),
+        overvote: overvote.floor(),
+        continuing_ballots: continuing.floor(),
+        transfers,
+        elected: newly_elected,
+        eliminated,
+        quota: Some(quota.floor()),
+        tie_breaks: Vec::new(),
+        exhausted: ExhaustedBallots {
+            blank: count.blank.floor(),
+            rankings_eliminated: count.exhausted(BallotEnd::NoMoreRankings).floor(),
+            overvote: overvote.floor(),
+            skipped_rankings: count.exhausted(BallotEnd::SkippedRankings).floor(),
+        },
+        bottom_two: None,
+        batch_elimination: None,
+        exact_quota: Some(quota),
+        keep_factors: elected
+            .iter()
+            .map(|c| KeepFactor {
+                candidate: *c,
+                keep_factor: keep[c],
+            })
+            .collect(),
+    }
+}
+
+fn tabulate_fractional(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+    rule: Rule,
+) -> Vec<TabulatorRound> {
+    let seats = options.seats();
+    let tie_breaker = TieBreaker::new(options.tie_break.as_deref().unwrap_or_default(), candidates);
+
+    // Candidates nobody ranked can't be elected, so they are left out.
+    let ranked: HashSet<CandidateId> = ballots.iter().flat_map(|b| b.ranked_candidates()).collect();
+    let mut keep: HashMap<CandidateId, FixedPoint> =
+        ranked.iter().map(|c| (*c, FixedPoint::ONE)).collect();
+    let mut previous_keep = keep.clone();
+    let mut elected: Vec<CandidateId> = Vec::new();
+    let mut eliminated: HashSet<CandidateId> = HashSet::new();
+    let mut rounds = Vec::new();
+
+    loop {
+        let count = converge(ballots, &mut keep, &elected, seats, rule);
+        let quota = count.quota(seats);
+        let transfers = if rounds.is_empty() {
+            Vec::new()
+        } else {
+            transfers(ballots, &previous_keep, &keep, rule)
+        };
+        previous_keep = keep.clone();
+
+        let mut standing: Vec<(CandidateId, FixedPoint)> = ranked
+            .iter()
+            .filter(|c| !eliminated.contains(c))
+            .map(|c| (*c, count.votes(*c)))
+            .collect();
+        standing.sort_by_key(|(c, votes)| (Reverse(*votes), *c));
+        let hopefuls: Vec<(CandidateId, FixedPoint)> = standing
+            .iter()
+            .filter(|(c, _)| !elected.contains(c))
+            .copied()
+            .collect();
+
+        let open_seats = seats as usize - elected.len();
+        let round = |newly_elected, eliminated| {
+            as_round(
+                &count,
+                quota,
+                &standing,
+                &elected,
+                &keep,
+                transfers.clone(),
+                newly_elected,
+                eliminated,
+            )
+        };
+
+        if hopefuls.len() <= open_seats {
+            // Every remaining candidate fills a seat.
+            rounds.push(round(
+                hopefuls.iter().map(|(c, _)| *c).collect(),
+                Vec::new(),
+            ));
+            break;
+        }
+
+        let newly_elected: Vec<CandidateId> = hopefuls
+            .iter()
+            .filter(|(_, votes)| *votes >= quota)
+            .take(open_seats)
+            .map(|(c, _)| *c)
+            .collect();
+
+        if newly_elected.len() == open_seats {
+            rounds.push(round(newly_elected, Vec::new()));
+            break;
+        } else if !newly_elected.is_empty() {
+            rounds.push(round(newly_elected.clone(), Vec::new()));
+            elected.extend(newly_elected);
+        } else {
+            let (last, tie_break) = tie_breaker.last_place(&hopefuls, &rounds);
+            let mut round = round(Vec::new(), vec![last]);
+            round.tie_breaks.extend(tie_break);
+            rounds.push(round);
+            eliminated.insert(last);
+            keep.insert(last, FixedPoint::ZERO);
+        }
+    }
+
+    rounds
+}
+
+/// Tabulate a multi-seat contest by Meek's method. Rather than moving whole
+/// ballots, each elected candidate keeps a fraction of the value of every
+/// ballot that reaches them, chosen so they hold exactly the quota, and
+/// passes the rest on. The quota is recalculated as ballots exhaust.
+pub fn tabulate_meek(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    tabulate_fractional(ballots, candidates, options, Rule::Meek)
+}
+
+/// Tabulate a multi-seat contest by Warren's method, which is like Meek's
+/// except that each elected candidate keeps a fraction of the ballot's full
+/// value rather than of the value that reaches them.
+pub fn tabulate_warren(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    tabulate_fractional(ballots, candidates, options, Rule::Warren)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::Ranking;
+    use crate::tabulator::tests::{ballots, candidates, votes};
+
+    fn seats(seats: u32) -> TabulationOptions {
+        TabulationOptions {
+            seats: Some(seats),
+            ..Default::default()
+        }
+    }
+
+    fn exact_votes(round: &TabulatorRound, candidate: u32) -> FixedPoint {
+        round
+            .allocations
+            .iter()
+            .find(|a| a.allocatee == Allocatee::Candidate(CandidateId(candidate)))
+            .and_then(|a| a.exact_votes)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_distribute() {
+        let ballot = &ballots(&[(1, &[0, 1, 2])])[0];
+        let half = FixedPoint::ONE / FixedPoint::from_int(2);
+        let keep: HashMap<CandidateId, FixedPoint> = vec![
+            (CandidateId(0), half),
+            (CandidateId(1), half),
+            (CandidateId(2), FixedPoint::ONE),
+        ]
+        .into_iter()
+        .collect();
+
+        let (meek, _) = distribute(ballot, &keep, Rule::Meek);
+        assert_eq!("0.250000000", meek[&CandidateId(1)].to_string());
+        assert_eq!("0.250000000", meek[&CandidateId(2)].to_string());
+
+        let (warren, _) = distribute(ballot, &keep, Rule::Warren);
+        assert_eq!("0.500000000", warren[&CandidateId(1)].to_string());
+        assert_eq!(None, warren.get(&CandidateId(2)));
+    }
+
+    #[test]
+    fn test_distribute_overvote() {
+        let ballot = NormalizedBallot::from_rankings(
+            "1".to_string(),
+            vec![
+                Ranking::Overvote(vec![CandidateId(0), CandidateId(1)]),
+                Ranking::Candidate(CandidateId(2)),
+            ],
+            BallotEnd::NoMoreRankings,
+        );
+        let mut keep: HashMap<CandidateId, FixedPoint> =
+            (0..3).map(|c| (CandidateId(c), FixedPoint::ONE)).collect();
+
+        // Both overvoted candidates are continuing, so the ballot exhausts.
+        let (shares, remaining) = distribute(&ballot, &keep, Rule::Meek);
+        assert!(shares.is_empty());
+        assert_eq!(FixedPoint::ONE, remaining);
+        assert_eq!(BallotEnd::Overvote, ballot.end_among(continuing(&keep)));
+
+        // Once candidate 0 is eliminated, it counts for candidate 1.
+        keep.insert(CandidateId(0), FixedPoint::ZERO);
+        let (shares, remaining) = distribute(&ballot, &keep, Rule::Meek);
+        assert_eq!(FixedPoint::ONE, shares[&CandidateId(1)]);
+        assert_eq!(FixedPoint::ZERO, remaining);
+    }
+
+    #[test]
+    fn test_meek_surplus() {
+        // The quota is a third of 100 votes. Candidate 0 keeps about 83% of
+        // each of their ballots and passes the rest to candidate 2.
+        let ballots = ballots(&[(40, &[0, 2]), (30, &[1]), (30, &[2])]);
+        let rounds = tabulate_meek(&ballots, &candidates(3), &seats(2));
+
+        assert_eq!(Some(33), rounds[0].quota);
+        assert_eq!(vec![CandidateId(0)], rounds[0].elected);
+
+        assert_eq!(CandidateId(0), rounds[1].keep_factors[0].candidate);
+        assert_eq!(36, votes(&rounds[1], 2));
+        let surplus = exact_votes(&rounds[1], 2) - FixedPoint::from_int(30);
+        assert_eq!(6, surplus.floor());
+        assert_eq!(CandidateId(0), rounds[1].transfers[0].from);
+        assert_eq!(
+            Allocatee::Candidate(CandidateId(2)),
+            rounds[1].transfers[0].to
+        );
+        assert_eq!(vec![CandidateId(2)], rounds[1].elected);
+    }
+
+    #[test]
+    fn test_elimination() {
+        // Nobody reaches the quota at first, so candidate 3 is eliminated and
+        // their votes go to candidate 1.
+        let ballots = ballots(&[(25, &[0]), (24, &[1]), (22, &[2]), (9, &[3, 1])]);
+        for tabulate in &[tabulate_meek, tabulate_warren] {
+            let rounds = tabulate(&ballots, &candidates(4), &seats(2));
+
+            assert_eq!(vec![CandidateId(3)], rounds[0].eliminated);
+            assert_eq!(33, votes(&rounds[1], 1));
+            assert_eq!(vec![CandidateId(1)], rounds[1].elected);
+
+            let elected: Vec<CandidateId> = rounds.iter().flat_map(|r| r.elected.clone()).collect();
+            assert_eq!(vec![CandidateId(1), CandidateId(0)], elected);
+        }
+    }
+}
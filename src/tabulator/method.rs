@@ -0,0 +1,221 @@
+use crate::model::election::{Candidate, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::tabulator::{
+    tabulate, tabulate_meek, tabulate_sequential, tabulate_stv, tabulate_warren, TabulatorRound,
+};
+
+/// A way of counting a contest's ballots to decide its winners.
+pub trait TabulationMethod {
+    /// Tabulate a contest. `ballots` are in the order they were cast, and
+    /// `grouped` are the same ballots with identical rankings merged, which
+    /// methods that don't depend on ballot order should count instead.
+    /// Returns the rounds of each separate count in the order they were run;
+    /// most methods count only once.
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        ballots: &[NormalizedBallot],
+        grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>>;
+
+    /// Whether the method fills more than one seat. Single-seat methods are
+    /// only used for contests with one seat, and multi-seat methods only for
+    /// contests with more.
+    fn multi_seat(&self) -> bool {
+        true
+    }
+}
+
+/// Instant-runoff voting for a single seat.
+struct Irv;
+
+impl TabulationMethod for Irv {
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        _ballots: &[NormalizedBallot],
+        grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>> {
+        vec![tabulate(grouped, candidates, options)]
+    }
+
+    fn multi_seat(&self) -> bool {
+        false
+    }
+}
+
+/// Single transferable vote with the Droop quota.
+struct Stv;
+
+impl TabulationMethod for Stv {
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        ballots: &[NormalizedBallot],
+        _grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>> {
+        // Which ballots make up a surplus depends on the order they were cast
+        // in, so STV is tabulated on the ballots as given.
+        vec![tabulate_stv(ballots, candidates, options)]
+    }
+}
+
+/// Instant-runoff voting run once per seat.
+struct SequentialIrv;
+
+impl TabulationMethod for SequentialIrv {
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        _ballots: &[NormalizedBallot],
+        grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>> {
+        tabulate_sequential(grouped, candidates, options)
+    }
+}
+
+/// Meek's method of single transferable vote.
+struct Meek;
+
+impl TabulationMethod for Meek {
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        _ballots: &[NormalizedBallot],
+        grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>> {
+        vec![tabulate_meek(grouped, candidates, options)]
+    }
+}
+
+/// Warren's method of single transferable vote.
+struct Warren;
+
+impl TabulationMethod for Warren {
+    fn tabulate(
+        &self,
+        candidates: &[Candidate],
+        _ballots: &[NormalizedBallot],
+        grouped: &[NormalizedBallot],
+        options: &TabulationOptions,
+    ) -> Vec<Vec<TabulatorRound>> {
+        vec![tabulate_warren(grouped, candidates, options)]
+    }
+}
+
+pub fn get_tabulation_method(method: &str) -> &'static dyn TabulationMethod {
+    match method {
+        "irv" => &Irv,
+        "stv" => &Stv,
+        "sequentialIrv" => &SequentialIrv,
+        "meek" => &Meek,
+        "warren" => &Warren,
+        _ => panic!("The tabulation method {} is not implemented.", method),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::NormalizedElection;
+    use crate::model::metadata::{EliminationMode, MultiSeatMethod};
+    use crate::tabulator::tests::{ballots, candidates};
+
+    #[test]
+    fn test_method_from_options() {
+        let election = NormalizedElection {
+            candidates: candidates(3),
+            ballots: ballots(&[(5, &[0, 1]), (4, &[1]), (3, &[2, 1])]),
+            withdrawn: Vec::new(),
+        };
+        let grouped = election.grouped_ballots();
+        let mut options = TabulationOptions {
+            seats: Some(2),
+            multi_seat: Some(MultiSeatMethod::SequentialIrv),
+            ..Default::default()
+        };
+        assert_eq!("sequentialIrv", options.tabulation_method());
+        let seat_rounds = get_tabulation_method("sequentialIrv").tabulate(
+            &election.candidates,
+            &election.ballots,
+            &grouped,
+            &options,
+        );
+        assert_eq!(2, seat_rounds.len());
+
+        options.method = Some("irv".to_string());
+        options.seats = Some(1);
+        assert_eq!("irv", options.tabulation_method());
+        let seat_rounds = get_tabulation_method("irv").tabulate(
+            &election.candidates,
+            &election.ballots,
+            &grouped,
+            &options,
+        );
+        assert_eq!(1, seat_rounds.len());
+
+        assert_eq!("irv", TabulationOptions::default().tabulation_method());
+    }
+
+    #[test]
+    fn test_standard_irv() {
+        let mut options = TabulationOptions {
+            elimination: Some(EliminationMode::Single),
+            ..Default::default()
+        };
+        assert!(options.is_standard_irv());
+
+        options.elimination = Some(EliminationMode::BottomTwoRunoff);
+        assert!(!options.is_standard_irv());
+
+        options.elimination = Some(EliminationMode::Batch);
+        options.seats = Some(2);
+        assert!(!options.is_standard_irv());
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one seat")]
+    fn test_zero_seats() {
+        let options = TabulationOptions {
+            seats: Some(0),
+            ..Default::default()
+        };
+        options.tabulation_method();
+    }
+
+    #[test]
+    #[should_panic(expected = "not implemented")]
+    fn test_unknown_method() {
+        let options = TabulationOptions {
+            method: Some("irb".to_string()),
+            ..Default::default()
+        };
+        options.tabulation_method();
+    }
+
+    #[test]
+    #[should_panic(expected = "fills one seat")]
+    fn test_irv_for_several_seats() {
+        let options = TabulationOptions {
+            seats: Some(3),
+            method: Some("irv".to_string()),
+            ..Default::default()
+        };
+        options.tabulation_method();
+    }
+
+    #[test]
+    #[should_panic(expected = "more than one seat")]
+    fn test_multi_seat_method_for_one_seat() {
+        let options = TabulationOptions {
+            method: Some("meek".to_string()),
+            ..Default::default()
+        };
+        options.tabulation_method();
+    }
+}
@@ -1,7 +1,22 @@
+mod meek;
+mod method;
 mod schema;
-
-use crate::model::election::{CandidateId, Choice, NormalizedBallot};
-pub use crate::tabulator::schema::{Allocatee, TabulatorAllocation, TabulatorRound, Transfer};
+mod sequential;
+mod stv;
+mod tie_break;
+
+use crate::condorcet::{pairwise_counts, PairwiseCounts};
+use crate::model::election::{BallotEnd, Candidate, CandidateId, Choice, NormalizedBallot};
+use crate::model::metadata::{EliminationMode, StoppingRule, TabulationOptions};
+pub use crate::tabulator::meek::{tabulate_meek, tabulate_warren};
+pub use crate::tabulator::method::get_tabulation_method;
+pub use crate::tabulator::schema::{
+    Allocatee, BatchElimination, BottomTwoComparison, ExhaustedBallots, TabulatorAllocation,
+    TabulatorRound, Transfer,
+};
+pub use crate::tabulator::sequential::tabulate_sequential;
+pub use crate::tabulator::stv::tabulate_stv;
+use crate::tabulator::tie_break::TieBreaker;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 /// Represents the number of ballots considered to be allocated to
@@ -14,13 +29,12 @@ struct Allocations {
 impl Allocations {
     pub fn new(mut votes: Vec<(CandidateId, u32)>, exhausted: u32) -> Allocations {
         // Sort descending by number of votes.
-        votes.sort_by(|a, b| (b.1).cmp(&a.1));
+        votes.sort_by_key(|b| std::cmp::Reverse(b.1));
 
         Allocations { votes, exhausted }
     }
 
     /// Returns true if a winner can be declared from this allocation.
-    #[allow(unused)]
     pub fn is_final(&self) -> bool {
         match self.votes.first() {
             Some((_, first_votes)) => {
@@ -38,11 +52,13 @@ impl Allocations {
             v.push(TabulatorAllocation {
                 allocatee: Allocatee::Candidate(id),
                 votes,
+                exact_votes: None,
             });
         }
         v.push(TabulatorAllocation {
             allocatee: Allocatee::Exhausted,
             votes: self.exhausted,
+            exact_votes: None,
         });
         v
     }
@@ -64,23 +80,30 @@ struct TabulatorState {
 
     /// Set of candidates who have already been eliminated prior to this round.
     eliminated: HashSet<CandidateId>,
+
+    /// Number of ballots that rank no candidates.
+    blank: u32,
 }
 
 impl TabulatorState {
     /// Obtain the `TabulatorRound` representation of a `TabulatorState`.
     /// The `TabulatorRound` representation is the one that is serialized
     /// into the report.
-    pub fn as_round(&self) -> TabulatorRound {
+    pub fn as_round(
+        &self,
+        elected: Vec<CandidateId>,
+        eliminated: Vec<CandidateId>,
+    ) -> TabulatorRound {
         let allocations = self.allocations();
         let undervote = self
             .candidate_ballots
             .get(&Choice::Undervote)
-            .map(|x| x.len() as u32)
+            .map(|x| ballot_count(x))
             .unwrap_or(0);
         let overvote = self
             .candidate_ballots
             .get(&Choice::Overvote)
-            .map(|x| x.len() as u32)
+            .map(|x| ballot_count(x))
             .unwrap_or(0);
         let continuing_ballots = allocations.continuing();
 
@@ -90,22 +113,34 @@ impl TabulatorState {
             overvote,
             continuing_ballots,
             transfers: self.transfers.clone(),
+            elected,
+            eliminated,
+            quota: None,
+            tie_breaks: Vec::new(),
+            exhausted: exhausted_ballots(&self.candidate_ballots, self.blank),
+            bottom_two: None,
+            batch_elimination: None,
+            exact_quota: None,
+            keep_factors: Vec::new(),
         }
     }
 
-    pub fn new(ballots: &[NormalizedBallot]) -> TabulatorState {
+    /// Allocate each ballot to its top choice, passing over the candidates
+    /// in `excluded` as if they had already been eliminated.
+    pub fn new(ballots: &[NormalizedBallot], excluded: &HashSet<CandidateId>) -> TabulatorState {
         let mut allocations: BTreeMap<Choice, Vec<NormalizedBallot>> = BTreeMap::new();
         for ballot in ballots {
-            let choice = ballot.top_vote();
-            allocations
-                .entry(choice)
-                .or_insert_with(Vec::new)
-                .push(ballot.clone());
+            let (ballot, choice) = match ballot.continuing_vote(excluded) {
+                Some(choice) => (ballot.clone(), choice),
+                None => next_preference(ballot.clone(), excluded),
+            };
+            allocations.entry(choice).or_default().push(ballot);
         }
         TabulatorState {
             candidate_ballots: allocations,
             transfers: Vec::new(),
-            eliminated: HashSet::new(),
+            eliminated: excluded.clone(),
+            blank: blank_ballots(ballots),
         }
     }
 
@@ -115,7 +150,7 @@ impl TabulatorState {
         let mut alloc: BTreeMap<CandidateId, u32> = BTreeMap::new();
         let mut exhausted: u32 = 0;
         for (choice, ballots) in &self.candidate_ballots {
-            let count = ballots.len() as u32;
+            let count = ballot_count(ballots);
             match choice {
                 Choice::Undervote => exhausted += count,
                 Choice::Overvote => exhausted += count,
@@ -130,112 +165,711 @@ impl TabulatorState {
         Allocations::new(votes, exhausted)
     }
 
-    pub fn do_elimination(self) -> TabulatorState {
+    /// Determine which group of candidates to eliminate at the end of this round.
+    /// An empty set means that only one candidate is eliminated, which may
+    /// require breaking a tie: the last-place candidate, or for a bottom-two
+    /// runoff, the loser of the runoff.
+    pub fn candidates_to_eliminate(&self, mode: EliminationMode) -> BTreeSet<CandidateId> {
         let allocations = self.allocations();
 
-        // Determine which candidates to eliminate.
-        let candidates_to_eliminate: BTreeSet<CandidateId> = {
-            let mut ai = allocations.votes.iter();
-            let mut remaining_votes = allocations.continuing();
+        match mode {
+            EliminationMode::Single | EliminationMode::BottomTwoRunoff => BTreeSet::new(),
+            EliminationMode::Batch => {
+                let mut ai = allocations.votes.iter();
+                let mut remaining_votes = allocations.continuing();
 
-            for (i, (_, votes)) in (&mut ai).enumerate() {
-                remaining_votes -= votes;
-                if votes > &remaining_votes && i > 0 {
-                    break;
+                for (i, (_, votes)) in (&mut ai).enumerate() {
+                    remaining_votes -= votes;
+                    if votes > &remaining_votes && i > 0 {
+                        break;
+                    }
                 }
-            }
 
-            ai.map(|d| d.0).collect()
-        };
+                // If the last two candidates are tied, no batch is safe to
+                // eliminate, so this is empty.
+                ai.map(|d| d.0).collect()
+            }
+            EliminationMode::Threshold(threshold) => {
+                // The leading candidate is never eliminated, even if they are
+                // under the threshold.
+                allocations.votes[1..]
+                    .iter()
+                    .filter(|(_, votes)| *votes < threshold)
+                    .map(|d| d.0)
+                    .collect()
+            }
+        }
+    }
 
-        let mut transfers: BTreeSet<Transfer> = BTreeSet::new();
+    pub fn do_elimination(self, candidates_to_eliminate: &BTreeSet<CandidateId>) -> TabulatorState {
+        let mut transfers: Vec<Transfer> = Vec::new();
         let mut eliminated = self.eliminated;
         eliminated.extend(candidates_to_eliminate.iter());
 
         let mut candidate_ballots = self.candidate_ballots;
 
         // For each eliminated candidate, re-allocate their votes.
-        for to_eliminate in &candidates_to_eliminate {
-            // Keep track of which candidate the eliminated candidate's votes go to,
-            // so that we can keep track of transfers.
-            let mut transfer_map: BTreeMap<Allocatee, u32> = BTreeMap::new();
-
+        for to_eliminate in candidates_to_eliminate {
             let ballots = candidate_ballots
                 .remove(&Choice::Vote(*to_eliminate))
                 .unwrap();
 
-            for mut ballot in ballots {
-                // Remove the top candidate from the ballot until we find one who has
-                // not been eliminated.
-                let new_choice = loop {
-                    ballot = ballot.pop_top_vote();
-                    let next_choice = ballot.top_vote();
-
-                    if let Choice::Vote(c) = next_choice {
-                        if !eliminated.contains(&c) {
-                            break next_choice;
-                        }
-                    } else {
-                        break next_choice;
-                    }
-                };
-
-                candidate_ballots
-                    .entry(new_choice)
-                    .or_insert_with(Vec::new)
-                    .push(ballot.clone());
-
-                *transfer_map
-                    .entry(Allocatee::from_choice(new_choice))
-                    .or_default() += 1;
-            }
+            transfers.extend(transfer_ballots(
+                &mut candidate_ballots,
+                *to_eliminate,
+                ballots,
+                &eliminated,
+            ));
+        }
 
-            // Add data about transfers from the eliminated candidate to the transfers list.
-            transfers.append(
-                &mut transfer_map
-                    .into_iter()
-                    .map(|(a, count)| Transfer {
-                        from: *to_eliminate,
-                        to: a,
-                        count,
-                    })
-                    .collect(),
-            );
-        }
-
-        // Collect transfers and sort them such that the transfers into the candidates
-        // with more votes come first.
-        // TODO: it might be cleaner to move this into a constructor of TabulatorState.
-        let mut transfers: Vec<Transfer> = transfers.into_iter().collect();
-        transfers.sort_by_key(|x| match x.to {
-            Allocatee::Exhausted => 0,
-            Allocatee::Candidate(c) => {
-                -(candidate_ballots.get(&Choice::Vote(c)).unwrap().len() as i32)
-            }
-        });
+        sort_transfers(&mut transfers, &candidate_ballots);
 
         TabulatorState {
             candidate_ballots,
             transfers,
             eliminated,
+            blank: self.blank,
+        }
+    }
+}
+
+/// Show why eliminating `eliminated` together is safe, given the votes of
+/// every continuing candidate in descending order.
+fn batch_elimination(
+    votes: &[(CandidateId, u32)],
+    eliminated: &BTreeSet<CandidateId>,
+) -> BatchElimination {
+    let (batch, rest): (Vec<_>, Vec<_>) = votes
+        .iter()
+        .copied()
+        .partition(|(c, _)| eliminated.contains(c));
+    let (next_lowest, next_lowest_votes) = *rest.last().unwrap();
+
+    BatchElimination {
+        eliminated: batch.iter().map(|(c, _)| *c).collect(),
+        eliminated_votes: batch.iter().map(|(_, v)| v).sum(),
+        next_lowest,
+        next_lowest_votes,
+    }
+}
+
+/// Return the number of ballots a pile stands for, counting each ballot by
+/// its weight.
+fn ballot_count(ballots: &[NormalizedBallot]) -> u32 {
+    ballots.iter().map(|b| b.count).sum()
+}
+
+/// Compare two candidates head-to-head. If they are tied, `last` loses.
+fn bottom_two_runoff(
+    counts: &PairwiseCounts,
+    second_last: CandidateId,
+    last: CandidateId,
+) -> BottomTwoComparison {
+    let votes = |a, b| counts.get(&(a, b)).copied().unwrap_or(0);
+    let (winner, loser) = if votes(last, second_last) > votes(second_last, last) {
+        (last, second_last)
+    } else {
+        (second_last, last)
+    };
+
+    BottomTwoComparison {
+        winner,
+        winner_votes: votes(winner, loser),
+        loser,
+        loser_votes: votes(loser, winner),
+    }
+}
+
+/// Return the number of ballots that rank no candidates.
+fn blank_ballots(ballots: &[NormalizedBallot]) -> u32 {
+    ballots
+        .iter()
+        .filter(|b| b.top_vote() == Choice::Undervote && b.end == BallotEnd::NoMoreRankings)
+        .map(|b| b.count)
+        .sum()
+}
+
+/// Break down the exhausted ballots in `candidate_ballots` by why they were
+/// exhausted, given the number of ballots that were blank to begin with.
+fn exhausted_ballots(
+    candidate_ballots: &BTreeMap<Choice, Vec<NormalizedBallot>>,
+    blank: u32,
+) -> ExhaustedBallots {
+    let mut exhausted = ExhaustedBallots {
+        blank,
+        ..Default::default()
+    };
+
+    // Ballots can reach an overvote before the end of their rankings, if
+    // their overvoted rankings are resolved during tabulation.
+    for ballot in candidate_ballots
+        .get(&Choice::Overvote)
+        .into_iter()
+        .flatten()
+    {
+        exhausted.overvote += ballot.count;
+    }
+    for ballot in candidate_ballots
+        .get(&Choice::Undervote)
+        .into_iter()
+        .flatten()
+    {
+        match ballot.end {
+            BallotEnd::NoMoreRankings => exhausted.rankings_eliminated += ballot.count,
+            BallotEnd::Overvote => exhausted.overvote += ballot.count,
+            BallotEnd::SkippedRankings => exhausted.skipped_rankings += ballot.count,
+        }
+    }
+    exhausted.rankings_eliminated -= blank;
+
+    exhausted
+}
+
+/// Remove candidates in `skip` from the top of the ballot (after removing its
+/// current top choice), and return the ballot along with its new top choice.
+/// Overvoted rankings are resolved against the candidates still continuing.
+fn next_preference(
+    mut ballot: NormalizedBallot,
+    skip: &HashSet<CandidateId>,
+) -> (NormalizedBallot, Choice) {
+    loop {
+        ballot = ballot.pop_top_vote();
+        if let Some(next_choice) = ballot.continuing_vote(skip) {
+            return (ballot, next_choice);
         }
     }
 }
 
-pub fn tabulate(ballots: &[NormalizedBallot]) -> Vec<TabulatorRound> {
-    let mut state = TabulatorState::new(ballots);
+/// Re-allocate ballots currently attributed to `from` to their next choice
+/// that is not in `skip`, and return the resulting transfers.
+fn transfer_ballots(
+    candidate_ballots: &mut BTreeMap<Choice, Vec<NormalizedBallot>>,
+    from: CandidateId,
+    ballots: Vec<NormalizedBallot>,
+    skip: &HashSet<CandidateId>,
+) -> Vec<Transfer> {
+    // Keep track of which candidate the ballots go to, so that we can keep
+    // track of transfers.
+    let mut transfer_map: BTreeMap<Allocatee, u32> = BTreeMap::new();
+
+    for ballot in ballots {
+        let (ballot, new_choice) = next_preference(ballot, skip);
+
+        *transfer_map
+            .entry(Allocatee::from_choice(new_choice))
+            .or_default() += ballot.count;
+
+        candidate_ballots
+            .entry(new_choice)
+            .or_default()
+            .push(ballot);
+    }
+
+    transfer_map
+        .into_iter()
+        .map(|(a, count)| Transfer {
+            from,
+            to: a,
+            count,
+            exact_count: None,
+        })
+        .collect()
+}
+
+/// Sort transfers such that the transfers into the candidates with more votes
+/// come first.
+// TODO: it might be cleaner to move this into a constructor of TabulatorState.
+fn sort_transfers(
+    transfers: &mut [Transfer],
+    candidate_ballots: &BTreeMap<Choice, Vec<NormalizedBallot>>,
+) {
+    transfers.sort_by_key(|x| match x.to {
+        Allocatee::Exhausted => 0,
+        Allocatee::Candidate(c) => {
+            -(ballot_count(candidate_ballots.get(&Choice::Vote(c)).unwrap()) as i64)
+        }
+    });
+}
+
+pub fn tabulate(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    tabulate_excluding(ballots, candidates, options, &HashSet::new())
+}
+
+/// Tabulate as if the candidates in `excluded` were not on the ballot.
+fn tabulate_excluding(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+    excluded: &HashSet<CandidateId>,
+) -> Vec<TabulatorRound> {
+    let mode = options.elimination_mode();
+    let stopping_rule = options.stopping_rule.unwrap_or(StoppingRule::TwoCandidates);
+    let tie_breaker = TieBreaker::new(options.tie_break.as_deref().unwrap_or_default(), candidates);
+    let mut state = TabulatorState::new(ballots, excluded);
     let mut rounds = Vec::new();
 
+    // Rankings between two candidates don't change as others are eliminated,
+    // so a bottom-two runoff can use pairwise counts from the full ballots.
+    let counts = if mode == EliminationMode::BottomTwoRunoff {
+        let ids: Vec<CandidateId> = (0..candidates.len() as u32).map(CandidateId).collect();
+        Some(pairwise_counts(&ids, ballots))
+    } else {
+        None
+    };
+
     loop {
         let allocations = state.allocations();
-        rounds.push(state.as_round());
+        let remaining = allocations.votes.len();
 
-        if allocations.votes.len() <= 2 {
+        let done = match stopping_rule {
+            StoppingRule::Majority => remaining <= 2 || allocations.is_final(),
+            StoppingRule::TwoCandidates => remaining <= 2,
+            StoppingRule::OneCandidate => remaining <= 1,
+        };
+
+        if done {
+            let (winner, tie_breaks) = tie_breaker.first_place(&allocations.votes, &rounds);
+            let mut round = state.as_round(vec![winner], Vec::new());
+            round.tie_breaks = tie_breaks;
+            rounds.push(round);
             break;
         }
 
-        state = state.do_elimination();
+        let mut candidates_to_eliminate = state.candidates_to_eliminate(mode);
+        let batch = mode == EliminationMode::Batch && !candidates_to_eliminate.is_empty();
+        let mut tie_breaks = Vec::new();
+        let mut bottom_two = None;
+        if candidates_to_eliminate.is_empty() {
+            let (last, tie_break) = tie_breaker.last_place(&allocations.votes, &rounds);
+            tie_breaks.extend(tie_break);
+
+            match &counts {
+                Some(counts) => {
+                    let others: Vec<(CandidateId, u32)> = allocations
+                        .votes
+                        .iter()
+                        .filter(|(c, _)| *c != last)
+                        .copied()
+                        .collect();
+                    let (second_last, tie_break) = tie_breaker.last_place(&others, &rounds);
+                    tie_breaks.extend(tie_break);
+
+                    let comparison = bottom_two_runoff(counts, second_last, last);
+                    candidates_to_eliminate.insert(comparison.loser);
+                    bottom_two = Some(comparison);
+                }
+                None => {
+                    candidates_to_eliminate.insert(last);
+                }
+            }
+        }
+
+        let mut round = state.as_round(
+            Vec::new(),
+            candidates_to_eliminate.iter().copied().collect(),
+        );
+        if batch {
+            round.batch_elimination = Some(batch_elimination(
+                &allocations.votes,
+                &candidates_to_eliminate,
+            ));
+        }
+        round.tie_breaks = tie_breaks;
+        round.bottom_two = bottom_two;
+        rounds.push(round);
+
+        state = state.do_elimination(&candidates_to_eliminate);
     }
 
     rounds
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::model::election::{
+        BallotSource, CandidateType, CountingGroup, NormalizedElection, Ranking,
+    };
+    use crate::model::metadata::TieBreakRule;
+    use crate::tabulator::schema::TieBreakMethod;
+    use crate::util::Rng;
+    use std::time::Instant;
+
+    /// Build ballots from a list of (number of ballots, ranked candidates),
+    /// with one weighted ballot per entry.
+    pub fn ballots(spec: &[(u32, &[u32])]) -> Vec<NormalizedBallot> {
+        spec.iter()
+            .enumerate()
+            .map(|(i, (count, choices))| {
+                NormalizedBallot::new(
+                    i.to_string(),
+                    choices.iter().map(|c| CandidateId(*c)).collect(),
+                    BallotEnd::NoMoreRankings,
+                )
+                .with_count(*count)
+            })
+            .collect()
+    }
+
+    /// Build the same ballots as `ballots`, with one unweighted ballot per
+    /// ballot cast.
+    pub fn expanded_ballots(spec: &[(u32, &[u32])]) -> Vec<NormalizedBallot> {
+        ballots(spec)
+            .iter()
+            .flat_map(|ballot| (0..ballot.count).map(move |_| ballot.with_count(1)))
+            .collect()
+    }
+
+    /// Votes allocated to the given candidate in a round.
+    pub fn votes(round: &TabulatorRound, candidate: u32) -> u32 {
+        round
+            .allocations
+            .iter()
+            .find(|a| a.allocatee == Allocatee::Candidate(CandidateId(candidate)))
+            .map(|a| a.votes)
+            .unwrap_or(0)
+    }
+
+    /// Candidates named after their ids.
+    pub fn candidates(n: u32) -> Vec<Candidate> {
+        (0..n)
+            .map(|i| Candidate::new(i.to_string(), CandidateType::Regular))
+            .collect()
+    }
+
+    fn options(elimination: EliminationMode) -> TabulationOptions {
+        TabulationOptions {
+            elimination: Some(elimination),
+            ..Default::default()
+        }
+    }
+
+    const BALLOTS: &[(u32, &[u32])] = &[
+        (40, &[0]),
+        (35, &[1]),
+        (12, &[2, 1]),
+        (8, &[3, 2]),
+        (5, &[4, 3]),
+    ];
+
+    #[test]
+    fn test_batch_elimination() {
+        let rounds = tabulate(
+            &ballots(BALLOTS),
+            &candidates(5),
+            &options(EliminationMode::Batch),
+        );
+
+        // 5 + 8 + 12 < 35, so candidates 2, 3 and 4 are eliminated together.
+        assert_eq!(
+            vec![CandidateId(2), CandidateId(3), CandidateId(4)],
+            rounds[0].eliminated
+        );
+        assert_eq!(2, rounds.len());
+        assert_eq!(47, votes(&rounds[1], 1));
+
+        let batch = rounds[0].batch_elimination.as_ref().unwrap();
+        assert_eq!(
+            vec![CandidateId(2), CandidateId(3), CandidateId(4)],
+            batch.eliminated
+        );
+        assert_eq!(25, batch.eliminated_votes);
+        assert_eq!(CandidateId(1), batch.next_lowest);
+        assert_eq!(35, batch.next_lowest_votes);
+    }
+
+    #[test]
+    fn test_batch_elimination_tie() {
+        let rounds = tabulate(
+            &ballots(&[(40, &[0]), (30, &[1]), (15, &[2]), (15, &[3])]),
+            &candidates(4),
+            &options(EliminationMode::Batch),
+        );
+
+        assert_eq!(vec![CandidateId(3)], rounds[0].eliminated);
+        assert_eq!(None, rounds[0].batch_elimination);
+        assert_eq!(
+            vec![CandidateId(2), CandidateId(3)],
+            rounds[0].tie_breaks[0].tied
+        );
+        assert_eq!(
+            TieBreakMethod::CandidateOrder,
+            rounds[0].tie_breaks[0].method
+        );
+        assert_eq!(3, rounds.len());
+    }
+
+    #[test]
+    fn test_tied_final_round() {
+        let options = TabulationOptions {
+            tie_break: Some(vec![TieBreakRule::PreviousRoundsBackward]),
+            ..Default::default()
+        };
+        // Candidates 0 and 1 are tied in the final round, but candidate 0 had
+        // more votes in the round before.
+        let rounds = tabulate(
+            &ballots(&[(10, &[0]), (8, &[1]), (2, &[2, 1]), (1, &[3])]),
+            &candidates(4),
+            &options,
+        );
+
+        assert_eq!(vec![CandidateId(0)], rounds.last().unwrap().elected);
+        assert_eq!(CandidateId(1), rounds.last().unwrap().tie_breaks[0].loser);
+    }
+
+    #[test]
+    fn test_single_elimination() {
+        let rounds = tabulate(
+            &ballots(BALLOTS),
+            &candidates(5),
+            &options(EliminationMode::Single),
+        );
+
+        assert_eq!(4, rounds.len());
+        assert_eq!(vec![CandidateId(4)], rounds[0].eliminated);
+        assert_eq!(vec![CandidateId(2)], rounds[1].eliminated);
+        assert_eq!(vec![CandidateId(3)], rounds[2].eliminated);
+        assert_eq!(vec![CandidateId(1)], rounds[3].elected);
+    }
+
+    #[test]
+    fn test_threshold_elimination() {
+        let rounds = tabulate(
+            &ballots(BALLOTS),
+            &candidates(5),
+            &options(EliminationMode::Threshold(12)),
+        );
+
+        assert_eq!(vec![CandidateId(3), CandidateId(4)], rounds[0].eliminated);
+        assert_eq!(vec![CandidateId(2)], rounds[1].eliminated);
+        assert_eq!(3, rounds.len());
+    }
+
+    #[test]
+    fn test_bottom_two_runoff() {
+        // Candidate 2 has the fewest first choices but is preferred to every
+        // other candidate, so they survive each runoff and win.
+        let ballots = ballots(&[(40, &[0, 2]), (35, &[1, 2]), (10, &[2]), (15, &[3, 2])]);
+        let rounds = tabulate(
+            &ballots,
+            &candidates(4),
+            &options(EliminationMode::BottomTwoRunoff),
+        );
+
+        assert_eq!(
+            Some(BottomTwoComparison {
+                winner: CandidateId(2),
+                winner_votes: 85,
+                loser: CandidateId(3),
+                loser_votes: 15,
+            }),
+            rounds[0].bottom_two
+        );
+        assert_eq!(vec![CandidateId(1)], rounds[1].eliminated);
+        assert_eq!(vec![CandidateId(2)], rounds[2].elected);
+        assert_eq!(None, rounds[2].bottom_two);
+
+        let irv = tabulate(&ballots, &candidates(4), &options(EliminationMode::Single));
+        assert_eq!(vec![CandidateId(2)], irv[0].eliminated);
+        assert_eq!(vec![CandidateId(0)], irv.last().unwrap().elected);
+    }
+
+    #[test]
+    fn test_stopping_rules() {
+        let ballots = ballots(&[(60, &[0]), (25, &[1]), (15, &[2, 1])]);
+        let rounds = |stopping_rule| {
+            let options = TabulationOptions {
+                stopping_rule: Some(stopping_rule),
+                elimination: Some(EliminationMode::Single),
+                ..Default::default()
+            };
+            tabulate(&ballots, &candidates(3), &options)
+        };
+
+        assert_eq!(1, rounds(StoppingRule::Majority).len());
+        assert_eq!(2, rounds(StoppingRule::TwoCandidates).len());
+
+        let rounds = rounds(StoppingRule::OneCandidate);
+        assert_eq!(3, rounds.len());
+        assert_eq!(vec![CandidateId(1)], rounds[1].eliminated);
+        assert_eq!(vec![CandidateId(0)], rounds[2].elected);
+        assert_eq!(60, votes(&rounds[2], 0));
+    }
+
+    #[test]
+    fn test_weighted_ballots() {
+        for mode in &[EliminationMode::Single, EliminationMode::Batch] {
+            let weighted = tabulate(&ballots(BALLOTS), &candidates(5), &options(*mode));
+            let expanded = tabulate(&expanded_ballots(BALLOTS), &candidates(5), &options(*mode));
+            assert_eq!(expanded, weighted);
+        }
+    }
+
+    #[test]
+    fn test_grouped_ballots() {
+        let election = NormalizedElection {
+            candidates: candidates(5),
+            ballots: expanded_ballots(BALLOTS),
+            withdrawn: Vec::new(),
+        };
+        let grouped = election.grouped_ballots();
+
+        assert_eq!(BALLOTS.len(), grouped.len());
+        assert_eq!(
+            tabulate(
+                &election.ballots,
+                &election.candidates,
+                &options(EliminationMode::Single)
+            ),
+            tabulate(
+                &grouped,
+                &election.candidates,
+                &options(EliminationMode::Single)
+            )
+        );
+    }
+
+    #[test]
+    fn test_grouped_ballot_sources() {
+        let mut ballots = expanded_ballots(&[(3, &[0, 1])]);
+        for (ballot, precinct) in ballots.iter_mut().zip(&["1", "1", "2"]) {
+            ballot.source = BallotSource {
+                precinct: Some(precinct.to_string()),
+                counting_group: Some(CountingGroup::VoteByMail),
+                batch: None,
+            };
+        }
+        let election = NormalizedElection {
+            candidates: candidates(2),
+            ballots,
+            withdrawn: Vec::new(),
+        };
+        let grouped = election.grouped_ballots();
+
+        assert_eq!(1, grouped.len());
+        assert_eq!(
+            BallotSource {
+                precinct: None,
+                counting_group: Some(CountingGroup::VoteByMail),
+                batch: None,
+            },
+            grouped[0].source
+        );
+    }
+
+    /// Compare tabulating a large election ballot by ballot against
+    /// tabulating it after grouping identical rankings. Run with
+    /// `cargo test --release -- --ignored bench_grouped_ballots --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_grouped_ballots() {
+        let mut rng = Rng::new(2020);
+        let ballots = (0..2_000_000)
+            .map(|i| {
+                let mut ranking: Vec<CandidateId> = (0..8).map(CandidateId).collect();
+                rng.shuffle(&mut ranking);
+                ranking.truncate(1 + rng.below(3) as usize);
+                NormalizedBallot::new(i.to_string(), ranking, BallotEnd::NoMoreRankings)
+            })
+            .collect();
+        let election = NormalizedElection {
+            candidates: candidates(8),
+            ballots,
+            withdrawn: Vec::new(),
+        };
+        let options = options(EliminationMode::Single);
+
+        let start = Instant::now();
+        let ungrouped = tabulate(&election.ballots, &election.candidates, &options);
+        let ungrouped_time = start.elapsed();
+
+        let start = Instant::now();
+        let grouped_ballots = election.grouped_ballots();
+        let grouped = tabulate(&grouped_ballots, &election.candidates, &options);
+        let grouped_time = start.elapsed();
+
+        eprintln!(
+            "{} ballots, {} distinct rankings: {:?} ungrouped, {:?} grouped",
+            election.ballots.len(),
+            grouped_ballots.len(),
+            ungrouped_time,
+            grouped_time
+        );
+        assert_eq!(ungrouped, grouped);
+    }
+
+    #[test]
+    fn test_exhausted_ballots() {
+        let mut ballots = ballots(&[(40, &[0]), (30, &[1]), (10, &[2, 1]), (5, &[2]), (2, &[])]);
+        ballots.push(
+            NormalizedBallot::new("o".into(), vec![CandidateId(2)], BallotEnd::Overvote)
+                .with_count(4),
+        );
+        ballots.push(
+            NormalizedBallot::new("s".into(), vec![], BallotEnd::SkippedRankings).with_count(3),
+        );
+        let rounds = tabulate(&ballots, &candidates(3), &options(EliminationMode::Single));
+
+        let first = ExhaustedBallots {
+            blank: 2,
+            rankings_eliminated: 0,
+            overvote: 0,
+            skipped_rankings: 3,
+        };
+        assert_eq!(first, rounds[0].exhausted);
+
+        // Candidate 2 is eliminated, exhausting the ballots that rank only
+        // them or that reach an overvote after them.
+        let second = ExhaustedBallots {
+            rankings_eliminated: 5,
+            overvote: 4,
+            ..first
+        };
+        assert_eq!(second, rounds[1].exhausted);
+        assert_eq!(14, rounds[1].undervote + rounds[1].overvote);
+    }
+
+    #[test]
+    fn test_overvote_resolved_in_tabulation() {
+        let overvoted = |id: &str, candidates: &[u32], count: u32| {
+            let rankings = vec![
+                Ranking::Candidate(CandidateId(3)),
+                Ranking::Overvote(candidates.iter().map(|c| CandidateId(*c)).collect()),
+            ];
+            NormalizedBallot::from_rankings(id.into(), rankings, BallotEnd::NoMoreRankings)
+                .with_count(count)
+        };
+        let mut ballots = ballots(&[(40, &[0]), (30, &[1]), (20, &[2])]);
+        ballots.push(overvoted("a", &[1, 2], 6));
+        ballots.push(overvoted("b", &[3, 2], 4));
+        let rounds = tabulate(&ballots, &candidates(4), &options(EliminationMode::Single));
+
+        // Once candidate 3 is eliminated, ballots overvoting 1 and 2 are
+        // exhausted, while ballots overvoting 3 and 2 count for 2.
+        assert_eq!(24, votes(&rounds[1], 2));
+        assert_eq!(6, rounds[1].exhausted.overvote);
+        assert_eq!(0, rounds[1].exhausted.rankings_eliminated);
+
+        // Then candidate 2 is eliminated and those ballots run out of
+        // rankings.
+        assert_eq!(24, rounds[2].exhausted.rankings_eliminated);
+        assert_eq!(6, rounds[2].exhausted.overvote);
+    }
+
+    #[test]
+    fn test_eager_option() {
+        let legacy = TabulationOptions {
+            eager: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(EliminationMode::Single, legacy.elimination_mode());
+        assert_eq!(
+            EliminationMode::Batch,
+            TabulationOptions::default().elimination_mode()
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::model::election::{CandidateId, Choice};
+use crate::util::FixedPoint;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorRound {
     pub allocations: Vec<TabulatorAllocation>,
@@ -10,14 +11,134 @@ pub struct TabulatorRound {
     pub overvote: u32,
     pub continuing_ballots: u32,
     pub transfers: Vec<Transfer>,
-    //eliminated: Vec<u32>,
+
+    /// Candidates declared elected at the end of this round.
+    #[serde(default)]
+    pub elected: Vec<CandidateId>,
+
+    /// Candidates eliminated at the end of this round. Their ballots are
+    /// transferred in the following round.
+    #[serde(default)]
+    pub eliminated: Vec<CandidateId>,
+
+    /// Number of votes needed to be elected, for multi-seat contests.
+    #[serde(default)]
+    pub quota: Option<u32>,
+
+    /// Ties broken to decide who was elected or eliminated in this round.
+    #[serde(default)]
+    pub tie_breaks: Vec<TieBreak>,
+
+    /// Exhausted ballots broken down by why they stopped counting.
+    #[serde(default)]
+    pub exhausted: ExhaustedBallots,
+
+    /// Head-to-head comparison that decided this round's elimination, for
+    /// bottom-two runoff tabulation.
+    #[serde(default)]
+    pub bottom_two: Option<BottomTwoComparison>,
+
+    /// Why it was safe to eliminate this round's candidates together, for
+    /// batch elimination.
+    #[serde(default)]
+    pub batch_elimination: Option<BatchElimination>,
+
+    /// The exact quota, for methods that count fractions of ballots.
+    /// `quota` is then rounded down.
+    #[serde(default)]
+    pub exact_quota: Option<FixedPoint>,
+
+    /// Share of each ballot's value kept by each elected candidate, for
+    /// methods that count fractions of ballots.
+    #[serde(default)]
+    pub keep_factors: Vec<KeepFactor>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct KeepFactor {
+    pub candidate: CandidateId,
+    pub keep_factor: FixedPoint,
+}
+
+/// Head-to-head comparison of the two last-place candidates of a round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BottomTwoComparison {
+    /// Candidate kept in the contest.
+    pub winner: CandidateId,
+    /// Number of ballots ranking the winner above the loser.
+    pub winner_votes: u32,
+    /// Candidate eliminated.
+    pub loser: CandidateId,
+    /// Number of ballots ranking the loser above the winner.
+    pub loser_votes: u32,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Candidates eliminated together in one round. This is safe because even if
+/// all their votes went to the same one of them, that candidate would still
+/// trail every other candidate, so none of them could win.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchElimination {
+    /// Candidates eliminated, from most to fewest votes.
+    pub eliminated: Vec<CandidateId>,
+    /// Combined votes of the eliminated candidates.
+    pub eliminated_votes: u32,
+    /// Candidate with the fewest votes among those not eliminated.
+    pub next_lowest: CandidateId,
+    /// Votes of `next_lowest`, which are more than `eliminated_votes`.
+    pub next_lowest_votes: u32,
+}
+
+/// Ballots that no longer count towards any candidate, by the reason they
+/// were exhausted. These add up to the round's `Exhausted` allocation.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExhaustedBallots {
+    /// Ballots that did not rank any candidate.
+    pub blank: u32,
+    /// Ballots whose ranked candidates have all been eliminated or elected.
+    pub rankings_eliminated: u32,
+    /// Ballots that reached an overvote.
+    pub overvote: u32,
+    /// Ballots that reached too many skipped rankings in a row.
+    pub skipped_rankings: u32,
+}
+
+/// How a tie was resolved.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TieBreakMethod {
+    PreviousRoundsForward,
+    PreviousRoundsBackward,
+    Lottery,
+    LotOrder,
+    /// No configured rule resolved the tie, so it went against the candidate
+    /// with the highest id.
+    CandidateOrder,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TieBreak {
+    /// Candidates who were tied.
+    pub tied: Vec<CandidateId>,
+    /// Candidate who lost the tie.
+    pub loser: CandidateId,
+    pub method: TieBreakMethod,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TabulatorAllocation {
     pub allocatee: Allocatee,
     pub votes: u32,
+
+    /// The exact number of votes, for methods that count fractions of
+    /// ballots. `votes` is then rounded down.
+    #[serde(default)]
+    pub exact_votes: Option<FixedPoint>,
 }
 
 #[derive(Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Copy, Debug)]
@@ -90,10 +211,15 @@ impl<'de> Deserialize<'de> for Allocatee {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Ord, PartialOrd, Eq)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Ord, PartialOrd, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Transfer {
     pub from: CandidateId,
     pub to: Allocatee,
     pub count: u32,
+
+    /// The exact value transferred, for methods that count fractions of
+    /// ballots. `count` is then rounded down.
+    #[serde(default)]
+    pub exact_count: Option<FixedPoint>,
 }
@@ -0,0 +1,94 @@
+use crate::model::election::{Candidate, CandidateId, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::tabulator::{tabulate_excluding, TabulatorRound};
+use std::collections::HashSet;
+
+/// Fill several seats by sequential IRV: run IRV once per seat, each time as
+/// if the candidates elected to earlier seats were not on the ballot. Returns
+/// the rounds of each seat's tabulation, in order. Stops early once no ballot
+/// counts for a candidate who hasn't been elected, so if no candidate is
+/// ranked there are no counts.
+pub fn tabulate_sequential(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+) -> Vec<Vec<TabulatorRound>> {
+    let seats = options.seats();
+    let mut elected: HashSet<CandidateId> = HashSet::new();
+    let mut seat_rounds = Vec::new();
+
+    for _ in 0..seats {
+        // A ballot whose remaining rankings overvote two or more candidates
+        // counts for none of them.
+        let has_votes = ballots
+            .iter()
+            .any(|b| !b.ranking_among(|c| !elected.contains(&c)).is_empty());
+        if !has_votes {
+            break;
+        }
+
+        let rounds = tabulate_excluding(ballots, candidates, options, &elected);
+        elected.extend(rounds.last().unwrap().elected.iter());
+        seat_rounds.push(rounds);
+    }
+
+    seat_rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::{BallotEnd, Ranking};
+    use crate::tabulator::tests::{ballots, candidates, votes};
+
+    #[test]
+    fn test_sequential() {
+        let options = TabulationOptions {
+            seats: Some(3),
+            ..Default::default()
+        };
+        let ballots = ballots(&[(40, &[0, 1]), (35, &[1, 0]), (5, &[1]), (25, &[2, 1])]);
+        let seat_rounds = tabulate_sequential(&ballots, &candidates(3), &options);
+
+        // Candidate 1 wins the first seat once candidate 2 is eliminated.
+        assert_eq!(2, seat_rounds[0].len());
+        assert_eq!(vec![CandidateId(1)], seat_rounds[0][1].elected);
+
+        // Ballots for candidate 1 count for their next choice for the second
+        // seat, or are exhausted if they have none.
+        assert_eq!(75, votes(&seat_rounds[1][0], 0));
+        assert_eq!(5, seat_rounds[1][0].exhausted.rankings_eliminated);
+        assert_eq!(vec![CandidateId(0)], seat_rounds[1][0].elected);
+
+        assert_eq!(vec![CandidateId(2)], seat_rounds[2][0].elected);
+        assert_eq!(3, seat_rounds.len());
+    }
+
+    #[test]
+    fn test_no_rankings() {
+        let options = TabulationOptions {
+            seats: Some(2),
+            ..Default::default()
+        };
+        let blank = ballots(&[(3, &[])]);
+        assert!(tabulate_sequential(&blank, &candidates(2), &options).is_empty());
+    }
+
+    #[test]
+    fn test_only_overvotes_left() {
+        let options = TabulationOptions {
+            seats: Some(2),
+            ..Default::default()
+        };
+        let mut ballots = ballots(&[(1, &[0]), (1, &[0])]);
+        ballots.push(NormalizedBallot::from_rankings(
+            "2".to_string(),
+            vec![Ranking::Overvote(vec![CandidateId(1), CandidateId(2)])],
+            BallotEnd::NoMoreRankings,
+        ));
+        let seat_rounds = tabulate_sequential(&ballots, &candidates(3), &options);
+
+        assert_eq!(1, seat_rounds.len());
+        assert_eq!(vec![CandidateId(0)], seat_rounds[0][0].elected);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::model::election::{Candidate, CandidateId, Choice, NormalizedBallot};
+use crate::model::metadata::TabulationOptions;
+use crate::tabulator::tie_break::TieBreaker;
+use crate::tabulator::{
+    ballot_count, blank_ballots, exhausted_ballots, next_preference, sort_transfers,
+    transfer_ballots, Allocations, TabulatorRound, Transfer,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// Return the Droop quota: the smallest number of votes such that no more
+/// than `seats` candidates can reach it.
+pub fn droop_quota(continuing: u32, seats: u32) -> u32 {
+    continuing / (seats + 1) + 1
+}
+
+/// Return how many of the first `position` ballots of a pile of `n` are
+/// transferred when `surplus` ballots are picked using the Cincinnati method
+/// of taking every (n / surplus)th ballot.
+fn selected_before(position: u64, n: u64, surplus: u64) -> u64 {
+    if surplus == 0 {
+        0
+    } else if n <= surplus {
+        position
+    } else {
+        // Ballot k (counting from zero) is picked if k = i * n / surplus - 1
+        // for some i in 1..=surplus.
+        (((position + 1) * surplus - 1) / n).min(surplus)
+    }
+}
+
+struct StvState {
+    /// Map from candidate to ballots attributed to that candidate at this round.
+    /// Elected candidates retain the ballots that make up their quota.
+    candidate_ballots: BTreeMap<Choice, Vec<NormalizedBallot>>,
+
+    /// Transfers incoming from the prior round.
+    transfers: Vec<Transfer>,
+
+    /// Candidates elected prior to this round, in order of election.
+    elected: Vec<CandidateId>,
+
+    /// Set of candidates who have already been eliminated prior to this round.
+    eliminated: HashSet<CandidateId>,
+
+    quota: u32,
+
+    /// Number of ballots that rank no candidates.
+    blank: u32,
+}
+
+impl StvState {
+    pub fn new(ballots: &[NormalizedBallot], seats: u32) -> StvState {
+        let mut candidate_ballots: BTreeMap<Choice, Vec<NormalizedBallot>> = BTreeMap::new();
+        let excluded = HashSet::new();
+        for ballot in ballots {
+            let (ballot, choice) = match ballot.continuing_vote(&excluded) {
+                Some(choice) => (ballot.clone(), choice),
+                None => next_preference(ballot.clone(), &excluded),
+            };
+            candidate_ballots.entry(choice).or_default().push(ballot);
+        }
+
+        let continuing = candidate_ballots
+            .iter()
+            .filter(|(choice, _)| matches!(choice, Choice::Vote(_)))
+            .map(|(_, ballots)| ballot_count(ballots))
+            .sum();
+
+        StvState {
+            candidate_ballots,
+            transfers: Vec::new(),
+            elected: Vec::new(),
+            eliminated: HashSet::new(),
+            quota: droop_quota(continuing, seats),
+            blank: blank_ballots(ballots),
+        }
+    }
+
+    pub fn allocations(&self) -> Allocations {
+        let mut votes: Vec<(CandidateId, u32)> = Vec::new();
+        let mut exhausted: u32 = 0;
+        for (choice, ballots) in &self.candidate_ballots {
+            match choice {
+                Choice::Vote(c) => votes.push((*c, ballot_count(ballots))),
+                _ => exhausted += ballot_count(ballots),
+            }
+        }
+
+        Allocations::new(votes, exhausted)
+    }
+
+    pub fn as_round(
+        &self,
+        elected: Vec<CandidateId>,
+        eliminated: Vec<CandidateId>,
+    ) -> TabulatorRound {
+        let allocations = self.allocations();
+        let count = |choice: Choice| {
+            self.candidate_ballots
+                .get(&choice)
+                .map(|x| ballot_count(x))
+                .unwrap_or(0)
+        };
+        let continuing_ballots = allocations.continuing();
+
+        TabulatorRound {
+            allocations: allocations.into_vec(),
+            undervote: count(Choice::Undervote),
+            overvote: count(Choice::Overvote),
+            continuing_ballots,
+            transfers: self.transfers.clone(),
+            elected,
+            eliminated,
+            quota: Some(self.quota),
+            tie_breaks: Vec::new(),
+            exhausted: exhausted_ballots(&self.candidate_ballots, self.blank),
+            bottom_two: None,
+            batch_elimination: None,
+            exact_quota: None,
+            keep_factors: Vec::new(),
+        }
+    }
+
+    /// Candidates who are neither elected nor eliminated, with their votes,
+    /// in descending order of votes.
+    fn hopefuls(&self) -> Vec<(CandidateId, u32)> {
+        self.allocations()
+            .votes
+            .into_iter()
+            .filter(|(c, _)| !self.elected.contains(c))
+            .collect()
+    }
+
+    /// Candidates whose ballots should no longer be counted for them.
+    fn skip(&self) -> HashSet<CandidateId> {
+        self.eliminated
+            .iter()
+            .chain(self.elected.iter())
+            .copied()
+            .collect()
+    }
+
+    /// Declare the given candidates elected and transfer their surplus ballots.
+    /// Only ballots that have a next preference for a hopeful candidate are
+    /// eligible to be transferred. A weighted ballot is split if only some of
+    /// the ballots it stands for are picked.
+    pub fn do_election(mut self, newly_elected: &[CandidateId]) -> StvState {
+        self.elected.extend(newly_elected);
+        let skip = self.skip();
+        let mut transfers: Vec<Transfer> = Vec::new();
+
+        for candidate in newly_elected {
+            let pile = self
+                .candidate_ballots
+                .remove(&Choice::Vote(*candidate))
+                .unwrap();
+            let surplus = ballot_count(&pile).saturating_sub(self.quota) as u64;
+
+            let (transferable, mut retained): (Vec<NormalizedBallot>, Vec<NormalizedBallot>) =
+                pile.into_iter().partition(|ballot| {
+                    matches!(next_preference(ballot.clone(), &skip).1, Choice::Vote(_))
+                });
+
+            let n = ballot_count(&transferable) as u64;
+            let mut position = 0;
+            let mut to_transfer = Vec::new();
+            for ballot in transferable {
+                let start = selected_before(position, n, surplus);
+                position += ballot.count as u64;
+                let selected = (selected_before(position, n, surplus) - start) as u32;
+
+                if selected == ballot.count {
+                    to_transfer.push(ballot);
+                } else if selected == 0 {
+                    retained.push(ballot);
+                } else {
+                    to_transfer.push(ballot.with_count(selected));
+                    retained.push(ballot.with_count(ballot.count - selected));
+                }
+            }
+
+            self.candidate_ballots
+                .insert(Choice::Vote(*candidate), retained);
+            transfers.extend(transfer_ballots(
+                &mut self.candidate_ballots,
+                *candidate,
+                to_transfer,
+                &skip,
+            ));
+        }
+
+        sort_transfers(&mut transfers, &self.candidate_ballots);
+        self.transfers = transfers;
+        self
+    }
+
+    /// Eliminate the given candidate and transfer their ballots.
+    pub fn do_elimination(mut self, candidate: CandidateId) -> StvState {
+        self.eliminated.insert(candidate);
+        let skip = self.skip();
+
+        let ballots = self
+            .candidate_ballots
+            .remove(&Choice::Vote(candidate))
+            .unwrap();
+        let mut transfers =
+            transfer_ballots(&mut self.candidate_ballots, candidate, ballots, &skip);
+
+        sort_transfers(&mut transfers, &self.candidate_ballots);
+        self.transfers = transfers;
+        self
+    }
+}
+
+/// Tabulate a multi-seat contest by single transferable vote, using the Droop
+/// quota and whole-ballot surplus transfers.
+pub fn tabulate_stv(
+    ballots: &[NormalizedBallot],
+    candidates: &[Candidate],
+    options: &TabulationOptions,
+) -> Vec<TabulatorRound> {
+    let seats = options.seats();
+    let tie_breaker = TieBreaker::new(options.tie_break.as_deref().unwrap_or_default(), candidates);
+    let mut state = StvState::new(ballots, seats);
+    let mut rounds = Vec::new();
+
+    loop {
+        let hopefuls = state.hopefuls();
+        let open_seats = seats as usize - state.elected.len();
+
+        if hopefuls.len() <= open_seats {
+            // Every remaining candidate fills a seat.
+            let elected = hopefuls.iter().map(|(c, _)| *c).collect();
+            rounds.push(state.as_round(elected, Vec::new()));
+            break;
+        }
+
+        let newly_elected: Vec<CandidateId> = hopefuls
+            .iter()
+            .filter(|(_, votes)| *votes >= state.quota)
+            .take(open_seats)
+            .map(|(c, _)| *c)
+            .collect();
+
+        if newly_elected.len() == open_seats {
+            rounds.push(state.as_round(newly_elected, Vec::new()));
+            break;
+        } else if !newly_elected.is_empty() {
+            rounds.push(state.as_round(newly_elected.clone(), Vec::new()));
+            state = state.do_election(&newly_elected);
+        } else {
+            let (last, tie_break) = tie_breaker.last_place(&hopefuls, &rounds);
+            let mut round = state.as_round(Vec::new(), vec![last]);
+            round.tie_breaks.extend(tie_break);
+            rounds.push(round);
+            state = state.do_elimination(last);
+        }
+    }
+
+    rounds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tabulator::tests::{ballots, candidates, expanded_ballots, votes};
+
+    fn seats(seats: u32) -> TabulationOptions {
+        TabulationOptions {
+            seats: Some(seats),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_droop_quota() {
+        assert_eq!(34, droop_quota(100, 2));
+        assert_eq!(51, droop_quota(100, 1));
+        assert_eq!(26, droop_quota(101, 3));
+    }
+
+    #[test]
+    fn test_selected_before() {
+        let picked: Vec<u64> = (0..10)
+            .filter(|k| selected_before(k + 1, 10, 3) > selected_before(*k, 10, 3))
+            .collect();
+        assert_eq!(vec![2, 5, 9], picked);
+        assert_eq!(4, selected_before(4, 4, 6));
+        assert_eq!(0, selected_before(4, 10, 0));
+    }
+
+    #[test]
+    fn test_exact_quota() {
+        // Quota is 26. Candidate 0 reaches it exactly, so nothing transfers.
+        let ballots = ballots(&[(26, &[0, 1]), (20, &[1]), (30, &[2]), (24, &[3])]);
+        let rounds = tabulate_stv(&ballots, &candidates(4), &seats(3));
+
+        assert_eq!(Some(26), rounds[0].quota);
+        assert_eq!(vec![CandidateId(2), CandidateId(0)], rounds[0].elected);
+        assert_eq!(26, votes(&rounds[1], 0));
+        assert_eq!(20, votes(&rounds[1], 1));
+        assert!(rounds[1].transfers.is_empty());
+    }
+
+    #[test]
+    fn test_surplus_transfer() {
+        // Quota is 34. Candidate 0 has a surplus of 6, which goes to candidate 2.
+        let ballots = ballots(&[(40, &[0, 2]), (30, &[1]), (30, &[2])]);
+        let rounds = tabulate_stv(&ballots, &candidates(4), &seats(2));
+
+        assert_eq!(Some(34), rounds[0].quota);
+        assert_eq!(vec![CandidateId(0)], rounds[0].elected);
+
+        assert_eq!(34, votes(&rounds[1], 0));
+        assert_eq!(36, votes(&rounds[1], 2));
+        assert_eq!(vec![CandidateId(2)], rounds[1].elected);
+        assert_eq!(2, rounds.len());
+    }
+
+    #[test]
+    fn test_weighted_surplus_transfer() {
+        // Quota is 34. Of the 20 transferable ballots, 6 are picked: 3 from
+        // each weighted ballot, so candidates 2 and 3 get 3 votes each.
+        let spec: &[(u32, &[u32])] = &[(20, &[0]), (10, &[0, 2]), (10, &[0, 3]), (30, &[1])];
+        let spec = [spec, &[(15, &[2]), (15, &[3])]].concat();
+        let weighted = tabulate_stv(&ballots(&spec), &candidates(4), &seats(2));
+
+        assert_eq!(18, votes(&weighted[1], 2));
+        assert_eq!(18, votes(&weighted[1], 3));
+        assert_eq!(
+            tabulate_stv(&expanded_ballots(&spec), &candidates(4), &seats(2)),
+            weighted
+        );
+    }
+
+    #[test]
+    fn test_elimination() {
+        // Quota is 27. Nobody reaches it at first, so candidate 3 is eliminated.
+        let ballots = ballots(&[(25, &[0]), (24, &[1]), (22, &[2]), (9, &[3, 1])]);
+        let rounds = tabulate_stv(&ballots, &candidates(4), &seats(2));
+
+        assert!(rounds[0].elected.is_empty());
+        assert_eq!(vec![CandidateId(3)], rounds[0].eliminated);
+        assert_eq!(33, votes(&rounds[1], 1));
+        assert_eq!(vec![CandidateId(1)], rounds[1].elected);
+
+        let elected: Vec<CandidateId> = rounds.iter().flat_map(|r| r.elected.clone()).collect();
+        assert_eq!(vec![CandidateId(1), CandidateId(0)], elected);
+    }
+}
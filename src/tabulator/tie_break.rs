@@ -0,0 +1,280 @@
+use crate::model::election::{Candidate, CandidateId};
+use crate::model::metadata::TieBreakRule;
+use crate::tabulator::schema::{Allocatee, TabulatorRound, TieBreak, TieBreakMethod};
+use crate::util::{FixedPoint, Rng};
+
+enum Rule {
+    PreviousRoundsForward,
+    PreviousRoundsBackward,
+    /// Candidates ordered from the one who wins ties to the one who loses them.
+    Order(Vec<CandidateId>, TieBreakMethod),
+}
+
+/// Breaks ties between candidates according to an election's tie-break rules.
+pub struct TieBreaker {
+    rules: Vec<Rule>,
+}
+
+fn votes_in_round(round: &TabulatorRound, candidate: CandidateId) -> FixedPoint {
+    round
+        .allocations
+        .iter()
+        .find(|a| a.allocatee == Allocatee::Candidate(candidate))
+        .map(|a| {
+            a.exact_votes
+                .unwrap_or_else(|| FixedPoint::from_int(a.votes))
+        })
+        .unwrap_or(FixedPoint::ZERO)
+}
+
+/// Narrow `tied` down to the candidates with the fewest votes in the first of
+/// the given rounds in which their votes differ.
+fn fewest_votes<'a>(
+    tied: &[CandidateId],
+    rounds: impl Iterator<Item = &'a TabulatorRound>,
+) -> Vec<CandidateId> {
+    for round in rounds {
+        let votes: Vec<FixedPoint> = tied.iter().map(|c| votes_in_round(round, *c)).collect();
+        let min = *votes.iter().min().unwrap();
+
+        if votes.iter().any(|v| *v != min) {
+            return tied
+                .iter()
+                .zip(votes)
+                .filter(|(_, v)| *v == min)
+                .map(|(c, _)| *c)
+                .collect();
+        }
+    }
+
+    tied.to_vec()
+}
+
+/// Narrow `tied` down to the candidate latest in `order`. Candidates missing
+/// from `order` come after every candidate in it.
+fn latest_in_order(tied: &[CandidateId], order: &[CandidateId]) -> Vec<CandidateId> {
+    let position = |c: &CandidateId| order.iter().position(|d| d == c).unwrap_or(order.len());
+    let latest = tied.iter().map(position).max().unwrap();
+
+    tied.iter()
+        .filter(|c| position(c) == latest)
+        .copied()
+        .collect()
+}
+
+impl TieBreaker {
+    pub fn new(rules: &[TieBreakRule], candidates: &[Candidate]) -> TieBreaker {
+        let rules = rules
+            .iter()
+            .map(|rule| match rule {
+                TieBreakRule::PreviousRoundsForward => Rule::PreviousRoundsForward,
+                TieBreakRule::PreviousRoundsBackward => Rule::PreviousRoundsBackward,
+                TieBreakRule::Lottery(seed) => {
+                    let mut order: Vec<CandidateId> =
+                        (0..candidates.len() as u32).map(CandidateId).collect();
+                    Rng::new(*seed).shuffle(&mut order);
+                    Rule::Order(order, TieBreakMethod::Lottery)
+                }
+                TieBreakRule::LotOrder(names) => {
+                    let order = names
+                        .iter()
+                        .map(|name| {
+                            let index = candidates
+                                .iter()
+                                .position(|c| &c.name == name)
+                                .unwrap_or_else(|| {
+                                    panic!("Candidate {} in lot order is not in the contest.", name)
+                                });
+                            CandidateId(index as u32)
+                        })
+                        .collect();
+                    Rule::Order(order, TieBreakMethod::LotOrder)
+                }
+            })
+            .collect();
+
+        TieBreaker { rules }
+    }
+
+    /// Choose which of the `tied` candidates loses the tie, given the rounds
+    /// tabulated before the tie.
+    pub fn loser(&self, tied: &[CandidateId], rounds: &[TabulatorRound]) -> TieBreak {
+        let mut tied: Vec<CandidateId> = tied.to_vec();
+        tied.sort();
+        let mut remaining = tied.clone();
+
+        for rule in &self.rules {
+            let method = match rule {
+                Rule::PreviousRoundsForward => {
+                    remaining = fewest_votes(&remaining, rounds.iter());
+                    TieBreakMethod::PreviousRoundsForward
+                }
+                Rule::PreviousRoundsBackward => {
+                    remaining = fewest_votes(&remaining, rounds.iter().rev());
+                    TieBreakMethod::PreviousRoundsBackward
+                }
+                Rule::Order(order, method) => {
+                    remaining = latest_in_order(&remaining, order);
+                    *method
+                }
+            };
+
+            if remaining.len() == 1 {
+                return TieBreak {
+                    tied,
+                    loser: remaining[0],
+                    method,
+                };
+            }
+        }
+
+        TieBreak {
+            tied,
+            loser: *remaining.iter().max().unwrap(),
+            method: TieBreakMethod::CandidateOrder,
+        }
+    }
+
+    /// Choose the candidate in last place, breaking a tie if there is one.
+    pub fn last_place<V: Ord + Copy>(
+        &self,
+        votes: &[(CandidateId, V)],
+        rounds: &[TabulatorRound],
+    ) -> (CandidateId, Option<TieBreak>) {
+        let min = votes.iter().map(|d| d.1).min().unwrap();
+        let tied: Vec<CandidateId> = votes.iter().filter(|d| d.1 == min).map(|d| d.0).collect();
+
+        if let [candidate] = tied.as_slice() {
+            (*candidate, None)
+        } else {
+            let tie_break = self.loser(&tied, rounds);
+            (tie_break.loser, Some(tie_break))
+        }
+    }
+
+    /// Choose the candidate in first place, breaking ties one loser at a time
+    /// if several candidates share the most votes.
+    pub fn first_place<V: Ord + Copy>(
+        &self,
+        votes: &[(CandidateId, V)],
+        rounds: &[TabulatorRound],
+    ) -> (CandidateId, Vec<TieBreak>) {
+        let max = votes.iter().map(|d| d.1).max().unwrap();
+        let mut tied: Vec<CandidateId> = votes.iter().filter(|d| d.1 == max).map(|d| d.0).collect();
+        let mut tie_breaks = Vec::new();
+
+        while tied.len() > 1 {
+            let tie_break = self.loser(&tied, rounds);
+            tied.retain(|c| *c != tie_break.loser);
+            tie_breaks.push(tie_break);
+        }
+
+        (tied[0], tie_breaks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::election::CandidateType;
+    use crate::tabulator::TabulatorAllocation;
+
+    fn round(votes: &[u32]) -> TabulatorRound {
+        TabulatorRound {
+            allocations: votes
+                .iter()
+                .enumerate()
+                .map(|(i, v)| TabulatorAllocation {
+                    allocatee: Allocatee::Candidate(CandidateId(i as u32)),
+                    votes: *v,
+                    exact_votes: None,
+                })
+                .collect(),
+            undervote: 0,
+            overvote: 0,
+            continuing_ballots: votes.iter().sum(),
+            transfers: Vec::new(),
+            elected: Vec::new(),
+            eliminated: Vec::new(),
+            quota: None,
+            tie_breaks: Vec::new(),
+            exhausted: Default::default(),
+            bottom_two: None,
+            batch_elimination: None,
+            exact_quota: None,
+            keep_factors: Vec::new(),
+        }
+    }
+
+    fn candidates() -> Vec<Candidate> {
+        ["Alice", "Bob", "Carol"]
+            .iter()
+            .map(|name| Candidate::new(name.to_string(), CandidateType::Regular))
+            .collect()
+    }
+
+    const TIED: &[CandidateId] = &[CandidateId(0), CandidateId(1), CandidateId(2)];
+
+    #[test]
+    fn test_previous_rounds() {
+        let rounds = vec![round(&[5, 4, 5]), round(&[7, 8, 6])];
+
+        let forward = TieBreaker::new(&[TieBreakRule::PreviousRoundsForward], &candidates());
+        let tie_break = forward.loser(TIED, &rounds);
+        assert_eq!(CandidateId(1), tie_break.loser);
+        assert_eq!(TieBreakMethod::PreviousRoundsForward, tie_break.method);
+
+        let backward = TieBreaker::new(&[TieBreakRule::PreviousRoundsBackward], &candidates());
+        assert_eq!(CandidateId(2), backward.loser(TIED, &rounds).loser);
+    }
+
+    #[test]
+    fn test_fallback() {
+        // The first rule narrows the tie to candidates 0 and 2, and the lot
+        // order decides between them.
+        let rules = vec![
+            TieBreakRule::PreviousRoundsForward,
+            TieBreakRule::LotOrder(vec!["Carol".into(), "Alice".into()]),
+        ];
+        let tie_breaker = TieBreaker::new(&rules, &candidates());
+        let tie_break = tie_breaker.loser(TIED, &[round(&[5, 6, 5])]);
+        assert_eq!(CandidateId(0), tie_break.loser);
+        assert_eq!(TieBreakMethod::LotOrder, tie_break.method);
+
+        let unresolved = TieBreaker::new(&[], &candidates()).loser(TIED, &[]);
+        assert_eq!(CandidateId(2), unresolved.loser);
+        assert_eq!(TieBreakMethod::CandidateOrder, unresolved.method);
+    }
+
+    #[test]
+    fn test_lottery() {
+        let tie_breaker = TieBreaker::new(&[TieBreakRule::Lottery(42)], &candidates());
+        let tie_break = tie_breaker.loser(TIED, &[]);
+        assert_eq!(TieBreakMethod::Lottery, tie_break.method);
+
+        // The same seed always gives the same result.
+        let again = TieBreaker::new(&[TieBreakRule::Lottery(42)], &candidates());
+        assert_eq!(tie_break, again.loser(TIED, &[]));
+    }
+
+    #[test]
+    fn test_first_place() {
+        let tie_breaker = TieBreaker::new(
+            &[TieBreakRule::LotOrder(vec![
+                "Bob".into(),
+                "Carol".into(),
+                "Alice".into(),
+            ])],
+            &candidates(),
+        );
+        let votes = vec![
+            (CandidateId(0), 10),
+            (CandidateId(1), 10),
+            (CandidateId(2), 10),
+        ];
+        let (winner, tie_breaks) = tie_breaker.first_place(&votes, &[]);
+
+        assert_eq!(CandidateId(1), winner);
+        assert_eq!(2, tie_breaks.len());
+    }
+}
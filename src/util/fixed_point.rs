@@ -0,0 +1,186 @@
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
+
+/// Number of fractional units in one whole.
+const SCALE: u64 = 1_000_000_000;
+
+/// A non-negative number with nine decimal places, used to count fractions
+/// of ballots exactly. Multiplication and division round down.
+///
+/// Serialized as a decimal string, e.g. `"12.500000000"`, so that no
+/// precision is lost in JSON.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedPoint(u64);
+
+impl FixedPoint {
+    pub const ZERO: FixedPoint = FixedPoint(0);
+    pub const ONE: FixedPoint = FixedPoint(SCALE);
+    /// The smallest representable positive value.
+    pub const EPSILON: FixedPoint = FixedPoint(1);
+
+    /// Build a value from a number of billionths.
+    pub const fn from_raw(raw: u64) -> FixedPoint {
+        FixedPoint(raw)
+    }
+
+    pub fn from_int(n: u32) -> FixedPoint {
+        FixedPoint(n as u64 * SCALE)
+    }
+
+    /// Return the whole part, rounding down.
+    pub fn floor(self) -> u32 {
+        (self.0 / SCALE) as u32
+    }
+
+    /// Return `self * numerator / denominator`, rounded up.
+    pub fn mul_div_ceil(self, numerator: FixedPoint, denominator: FixedPoint) -> FixedPoint {
+        let product = self.0 as u128 * numerator.0 as u128;
+        let denominator = denominator.0 as u128;
+        FixedPoint(product.div_ceil(denominator) as u64)
+    }
+
+    pub fn saturating_sub(self, other: FixedPoint) -> FixedPoint {
+        FixedPoint(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Add for FixedPoint {
+    type Output = FixedPoint;
+
+    fn add(self, other: FixedPoint) -> FixedPoint {
+        FixedPoint(self.0 + other.0)
+    }
+}
+
+impl AddAssign for FixedPoint {
+    fn add_assign(&mut self, other: FixedPoint) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = FixedPoint;
+
+    fn sub(self, other: FixedPoint) -> FixedPoint {
+        FixedPoint(self.0 - other.0)
+    }
+}
+
+impl SubAssign for FixedPoint {
+    fn sub_assign(&mut self, other: FixedPoint) {
+        self.0 -= other.0;
+    }
+}
+
+impl Mul for FixedPoint {
+    type Output = FixedPoint;
+
+    fn mul(self, other: FixedPoint) -> FixedPoint {
+        FixedPoint((self.0 as u128 * other.0 as u128 / SCALE as u128) as u64)
+    }
+}
+
+impl Div for FixedPoint {
+    type Output = FixedPoint;
+
+    fn div(self, other: FixedPoint) -> FixedPoint {
+        FixedPoint((self.0 as u128 * SCALE as u128 / other.0 as u128) as u64)
+    }
+}
+
+impl Sum for FixedPoint {
+    fn sum<I: Iterator<Item = FixedPoint>>(iter: I) -> FixedPoint {
+        iter.fold(FixedPoint::ZERO, Add::add)
+    }
+}
+
+impl fmt::Display for FixedPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{:09}", self.0 / SCALE, self.0 % SCALE)
+    }
+}
+
+impl std::str::FromStr for FixedPoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<FixedPoint, String> {
+        let invalid = || format!("Invalid fixed-point number {}.", s);
+        let (whole, fraction) = match s.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (s, ""),
+        };
+        if fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let whole: u64 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u64 = format!("{:0<9}", fraction).parse().unwrap();
+        Ok(FixedPoint(whole * SCALE + fraction))
+    }
+}
+
+struct FixedPointVisitor;
+
+impl<'de> Visitor<'de> for FixedPointVisitor {
+    type Value = FixedPoint;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal number as a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        v.parse().map_err(E::custom)
+    }
+}
+
+impl Serialize for FixedPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for FixedPoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(FixedPointVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let third = FixedPoint::ONE / FixedPoint::from_int(3);
+        assert_eq!("0.333333333", third.to_string());
+        assert_eq!("0.999999999", (third + third + third).to_string());
+        assert_eq!("0.111111110", (third * third).to_string());
+        assert_eq!(
+            "0.333333334",
+            FixedPoint::ONE
+                .mul_div_ceil(FixedPoint::ONE, FixedPoint::from_int(3))
+                .to_string()
+        );
+        assert_eq!(12, FixedPoint::from_int(12).floor());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(FixedPoint(12_500_000_000)), "12.5".parse());
+        assert_eq!(Ok(FixedPoint::from_int(3)), "3".parse());
+        assert!("1.0000000001".parse::<FixedPoint>().is_err());
+        assert!("-1".parse::<FixedPoint>().is_err());
+    }
+}
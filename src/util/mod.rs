@@ -1,9 +1,13 @@
+mod fixed_point;
 mod hash;
 mod io;
 mod path;
+mod rng;
 mod string;
 
+pub use fixed_point::FixedPoint;
 pub use hash::hash_file;
 pub use io::{read_serialized, write_serialized};
 pub use path::get_files_from_path;
+pub use rng::Rng;
 pub use string::UnicodeString;